@@ -1,22 +1,74 @@
 use std::io;
+use std::path::PathBuf;
 use std::time::Instant;
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
 use ratatui::DefaultTerminal;
 
 use crate::dipswitch::DipSwitch;
-use crate::ui;
+use crate::format_search::FormatMatch;
+use crate::history::{HISTORY_LIMIT, History};
+use crate::plan::{PlanChoice, TogglePlan};
+use crate::presets::PresetStore;
+use crate::profile::Profile;
+use crate::solver::{self, ConfigTarget, GroupTarget, Solution};
+use crate::theme::Theme;
+use crate::ui::{self, HitMap, HitTarget};
 
 /// Main application state
 pub struct App {
+    pub profile: Profile,
     pub dipswitch: DipSwitch,
     pub animations: [SwitchAnimation; 8],
+    /// Switch with the keyboard focus (0-based)
+    pub focus: usize,
+    pub mode: Mode,
+    pub solver: SolverView,
+    pub table: TableView,
+    pub search: SearchView,
+    pub scope: ScopeView,
+    /// Switch settings reachable with undo/redo, including the current one
+    pub history: History<DipSwitch>,
+    /// Selected step in the history list
+    pub history_selected: usize,
+    pub presets: PresetView,
+    pub compare: CompareView,
+    pub plan: PlanView,
+    pub theme: Theme,
+    /// Clickable regions of the last rendered frame
+    pub hits: HitMap,
+    /// Cell where the left mouse button went down, while it is held
+    drag_origin: Option<(u16, u16)>,
     pub should_quit: bool,
 }
 
+/// Which view currently receives key events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Normal,
+    Solver,
+    Table,
+    Search,
+    History,
+    Presets,
+    Compare,
+    Plan,
+}
+
 impl App {
     pub fn new() -> Self {
+        Self::with_profile(Profile::gen10().clone())
+    }
+
+    /// Create the application for a specific device profile
+    pub fn with_profile(profile: Profile) -> Self {
+        let solver = SolverView::new(&profile);
         Self {
+            profile,
             dipswitch: DipSwitch::new(),
             animations: [
                 SwitchAnimation::new(false),
@@ -28,10 +80,33 @@ impl App {
                 SwitchAnimation::new(false),
                 SwitchAnimation::new(false),
             ],
+            focus: 0,
+            mode: Mode::Normal,
+            solver,
+            table: TableView::default(),
+            search: SearchView::default(),
+            scope: ScopeView::default(),
+            history: History::new(DipSwitch::new(), HISTORY_LIMIT),
+            history_selected: 0,
+            presets: PresetView::default(),
+            compare: CompareView::default(),
+            plan: PlanView::default(),
+            theme: Theme::default(),
+            hits: HitMap::default(),
+            drag_origin: None,
             should_quit: false,
         }
     }
 
+    /// Start from `dipswitch` instead of all switches LEFT, without animating
+    pub fn start_with(&mut self, dipswitch: DipSwitch) {
+        for (index, animation) in self.animations.iter_mut().enumerate() {
+            *animation = SwitchAnimation::new(dipswitch.get(index));
+        }
+        self.history = History::new(dipswitch.clone(), HISTORY_LIMIT);
+        self.dipswitch = dipswitch;
+    }
+
     /// Main application loop
     pub fn run(&mut self, mut terminal: DefaultTerminal) -> io::Result<()> {
         let target_fps = 60;
@@ -48,7 +123,9 @@ impl App {
             self.update_animations(delta);
 
             // Render
-            terminal.draw(|frame| ui::render(frame, self))?;
+            let mut hits = HitMap::default();
+            terminal.draw(|frame| hits = ui::render(frame, self))?;
+            self.hits = hits;
 
             // Handle input with timeout to maintain framerate
             let timeout = frame_duration.saturating_sub(last_frame.elapsed());
@@ -62,18 +139,99 @@ impl App {
 
     /// Update all animations based on delta time
     fn update_animations(&mut self, delta_time: f64) {
-        for animation in &mut self.animations {
+        for animation in self
+            .animations
+            .iter_mut()
+            .chain(&mut self.compare.animations)
+        {
             animation.update(delta_time);
         }
+
+        // Play the plan back one move at a time, pausing while switches move
+        if self.mode == Mode::Plan && self.plan.playing {
+            if self.animations.iter().any(|animation| animation.animating) {
+                self.plan.idle = 0.0;
+            } else {
+                self.plan.idle += delta_time;
+                if self.plan.idle >= PLAN_STEP_PAUSE {
+                    self.plan.idle = 0.0;
+                    self.plan_step_forward();
+                    self.plan.playing = self.plan.done < self.plan.plan.moves.len();
+                }
+            }
+        }
     }
 
-    /// Handle keyboard and other events
+    /// Handle keyboard, mouse and other events
     fn handle_events(&mut self) -> io::Result<()> {
-        match event::read()? {
+        self.handle_event(event::read()?);
+        Ok(())
+    }
+
+    /// Handle one event
+    fn handle_event(&mut self, event: Event) {
+        match event {
             Event::Key(key_event) => self.handle_key_event(key_event),
+            Event::Mouse(mouse_event) => self.handle_mouse_event(mouse_event),
+            _ => {}
+        }
+        // Every change made by an event becomes one undo step; undo and
+        // redo leave the switches at the history's current state
+        self.history.record(self.dipswitch.clone());
+    }
+
+    /// Handle mouse events against the regions of the last rendered frame
+    ///
+    /// Clicking a switch toggles it; dragging over switches sets each to the
+    /// side of the switch the pointer is on. While a plan is shown, clicks
+    /// follow the plan instead. Clicking a key hint acts like pressing that key.
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        let (column, row) = (mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.drag_origin = Some((column, row));
+                match self.hits.hit(column, row) {
+                    Some((_, HitTarget::Switch(index))) if self.mode == Mode::Plan => {
+                        self.click_plan_switch(index);
+                    }
+                    Some((_, HitTarget::Switch(index))) => {
+                        self.focus = index;
+                        self.toggle_switch(index);
+                    }
+                    Some((_, HitTarget::BankSwitch { bank, index })) => {
+                        (self.compare.bank, self.focus) = (bank, index);
+                        self.set_bank_switch(bank, index, !self.bank(bank).get(index));
+                    }
+                    Some((_, HitTarget::TableCell { row, column })) => {
+                        (self.table.row, self.table.column) = (row, column);
+                        self.apply_table_cell();
+                    }
+                    Some((_, HitTarget::Key(code))) => {
+                        self.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+                    }
+                    None => {}
+                }
+            }
+            // A plan is only followed one move at a time
+            MouseEventKind::Drag(MouseButton::Left) if self.mode == Mode::Plan => {}
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if self.drag_origin == Some((column, row)) {
+                    return;
+                }
+                self.drag_origin = None;
+                match self.hits.hit(column, row) {
+                    Some((area, HitTarget::Switch(index))) => {
+                        self.set_switch(index, column >= area.x + area.width / 2);
+                    }
+                    Some((area, HitTarget::BankSwitch { bank, index })) => {
+                        self.set_bank_switch(bank, index, column >= area.x + area.width / 2);
+                    }
+                    _ => {}
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.drag_origin = None,
             _ => {}
         }
-        Ok(())
     }
 
     /// Handle key press events
@@ -83,14 +241,69 @@ impl App {
             return;
         }
 
+        match self.mode {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Solver => self.handle_solver_key(key),
+            Mode::Table => self.handle_table_key(key),
+            Mode::Search => self.handle_search_key(key),
+            Mode::History => self.handle_history_key(key),
+            Mode::Presets => self.handle_presets_key(key),
+            Mode::Compare => self.handle_compare_key(key),
+            Mode::Plan => self.handle_plan_key(key),
+        }
+    }
+
+    /// Handle keys on the main switch view
+    fn handle_normal_key(&mut self, key: KeyEvent) {
         match key.code {
             KeyCode::Char(c @ '1'..='8') => {
                 let idx = (c as u8 - b'1') as usize;
-                self.toggle_switch(idx);
+                if idx < self.profile.switch_count() {
+                    self.toggle_switch(idx);
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => self.move_focus(-1),
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => self.move_focus(1),
+            KeyCode::Left | KeyCode::Char('h') => self.set_switch(self.focus, false),
+            KeyCode::Right | KeyCode::Char('l') => self.set_switch(self.focus, true),
+            KeyCode::Char(' ') | KeyCode::Enter => self.toggle_switch(self.focus),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => self.redo(),
+            KeyCode::Char('H') => {
+                self.history_selected = self.history.position();
+                self.mode = Mode::History;
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                self.compare.bank = 0;
+                self.mode = Mode::Compare;
+            }
+            KeyCode::Char('p') | KeyCode::Char('P') => {
+                self.presets.naming = None;
+                self.presets.message = None;
+                self.mode = Mode::Presets;
             }
             KeyCode::Char('r') | KeyCode::Char('R') => {
                 self.reset_switches();
             }
+            KeyCode::Char('f') | KeyCode::Char('F') => {
+                self.solver.open(&self.profile, &self.dipswitch);
+                self.mode = Mode::Solver;
+            }
+            KeyCode::Char('/') => {
+                self.search.open(&self.profile);
+                self.mode = Mode::Search;
+            }
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                let entry = self.profile.table_entry(&self.dipswitch);
+                (self.table.row, self.table.column) = self.profile.table_grid_cell(entry);
+                self.mode = Mode::Table;
+            }
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                self.scope.visible = !self.scope.visible;
+            }
+            KeyCode::Char('g') | KeyCode::Char('G') if self.scope.visible => {
+                self.scope.group = 1 - self.scope.group;
+            }
             KeyCode::Char('q') | KeyCode::Char('Q') => {
                 self.should_quit = true;
             }
@@ -98,6 +311,341 @@ impl App {
         }
     }
 
+    /// Handle keys in the reverse lookup popup
+    fn handle_solver_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Normal,
+            KeyCode::Tab | KeyCode::Right => self.solver.focus = self.solver.focus.next(),
+            KeyCode::BackTab | KeyCode::Left => self.solver.focus = self.solver.focus.prev(),
+            KeyCode::Up => self.solver.move_selection(&self.profile, -1),
+            KeyCode::Down => self.solver.move_selection(&self.profile, 1),
+            KeyCode::Char('m') | KeyCode::Char('M') => {
+                let plan = TogglePlan::to_config(
+                    &self.profile,
+                    &self.dipswitch,
+                    &self.solver.target,
+                    PlanChoice::FewestMoves,
+                );
+                if let Some(plan) = plan {
+                    self.start_plan(plan, Mode::Normal);
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(solution) = self.solver.selected_solution() {
+                    let target = solution.dipswitch.clone();
+                    self.apply_dipswitch(&target);
+                    self.mode = Mode::Normal;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Move the focus cursor by `delta` switches, wrapping around the bank
+    fn move_focus(&mut self, delta: isize) {
+        let count = self.profile.switch_count() as isize;
+        self.focus = (self.focus as isize + delta).rem_euclid(count) as usize;
+    }
+
+    /// Handle keys in the Table 2 browser
+    fn handle_table_key(&mut self, key: KeyEvent) {
+        let rows = 1 << self.profile.table_row_switches().len();
+        let columns = 1 << self.profile.table_column_switches().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('t') | KeyCode::Char('T') => {
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.table.row = (self.table.row + rows - 1) % rows,
+            KeyCode::Down | KeyCode::Char('j') => self.table.row = (self.table.row + 1) % rows,
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.table.column = (self.table.column + columns - 1) % columns;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.table.column = (self.table.column + 1) % columns
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => self.apply_table_cell(),
+            _ => {}
+        }
+    }
+
+    /// Handle keys in the format search popup; printable keys edit the query
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.mode = Mode::Normal,
+            KeyCode::Tab | KeyCode::BackTab => self.search.group = 1 - self.search.group,
+            KeyCode::Up => self.search.move_selection(-1),
+            KeyCode::Down => self.search.move_selection(1),
+            KeyCode::Backspace => {
+                self.search.query.pop();
+                self.search.update(&self.profile);
+            }
+            KeyCode::Char(c) => {
+                self.search.query.push(c);
+                self.search.update(&self.profile);
+            }
+            KeyCode::Enter => {
+                if let Some(found) = self.search.selected_match() {
+                    let target = self
+                        .search
+                        .setting_for(&self.profile, &self.dipswitch, found);
+                    self.apply_dipswitch(&target);
+                    self.mode = Mode::Normal;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle keys in the history list; Enter jumps to the selected step
+    fn handle_history_key(&mut self, key: KeyEvent) {
+        let steps = self.history.states().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('H') => self.mode = Mode::Normal,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.history_selected = self.history_selected.saturating_sub(1)
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.history_selected = (self.history_selected + 1).min(steps - 1);
+            }
+            KeyCode::Char('u') => {
+                self.undo();
+                self.history_selected = self.history.position();
+            }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.redo();
+                self.history_selected = self.history.position();
+            }
+            KeyCode::Enter => {
+                if let Some(target) = self.history.jump(self.history_selected).cloned() {
+                    self.apply_dipswitch(&target);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle keys in the preset picker; while a name is being typed,
+    /// printable keys edit it
+    fn handle_presets_key(&mut self, key: KeyEvent) {
+        if let Some(name) = &mut self.presets.naming {
+            match key.code {
+                KeyCode::Esc => self.presets.naming = None,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Enter => {
+                    let name = name.clone();
+                    self.presets.naming = None;
+                    self.presets.save_as(&name, &self.dipswitch);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let count = self.presets.store.presets().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('p') | KeyCode::Char('P') => {
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.presets.selected = self.presets.selected.saturating_sub(1)
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.presets.selected = (self.presets.selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Char('a') | KeyCode::Char('n') => {
+                self.presets.naming = Some(String::new());
+                self.presets.message = None;
+            }
+            KeyCode::Char('d') | KeyCode::Delete => self.presets.remove_selected(),
+            KeyCode::Enter => {
+                if let Some(preset) = self.presets.store.presets().get(self.presets.selected) {
+                    let target = preset.dipswitch.clone();
+                    self.apply_dipswitch(&target);
+                    self.mode = Mode::Normal;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle keys in the compare view; switch keys act on the selected bank
+    fn handle_compare_key(&mut self, key: KeyEvent) {
+        let bank = self.compare.bank;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('c') | KeyCode::Char('C') => {
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Tab | KeyCode::BackTab => self.compare.bank = 1 - bank,
+            KeyCode::Char(c @ '1'..='8') => {
+                let idx = (c as u8 - b'1') as usize;
+                if idx < self.profile.switch_count() {
+                    self.set_bank_switch(bank, idx, !self.bank(bank).get(idx));
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_focus(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_focus(1),
+            KeyCode::Left | KeyCode::Char('h') => self.set_bank_switch(bank, self.focus, false),
+            KeyCode::Right | KeyCode::Char('l') => self.set_bank_switch(bank, self.focus, true),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                self.set_bank_switch(bank, self.focus, !self.bank(bank).get(self.focus));
+            }
+            KeyCode::Char('>') => {
+                let current = self.dipswitch.clone();
+                self.compare.apply(&current);
+            }
+            KeyCode::Char('<') => {
+                let required = self.compare.required.clone();
+                self.apply_dipswitch(&required);
+            }
+            KeyCode::Char('m') | KeyCode::Char('M') => {
+                let plan =
+                    TogglePlan::between(&self.profile, &self.dipswitch, &self.compare.required);
+                self.start_plan(plan, Mode::Compare);
+            }
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => self.redo(),
+            _ => {}
+        }
+    }
+
+    /// Show `plan` for playback on the unit's switches; closing it returns
+    /// to `return_to`
+    fn start_plan(&mut self, plan: TogglePlan, return_to: Mode) {
+        self.plan = PlanView {
+            plan,
+            return_to,
+            ..PlanView::default()
+        };
+        if let Some(first) = self.plan.plan.moves.first() {
+            self.focus = first.switch;
+        }
+        self.mode = Mode::Plan;
+    }
+
+    /// Handle keys while a toggle plan is shown
+    fn handle_plan_key(&mut self, key: KeyEvent) {
+        let finished = self.plan.done == self.plan.plan.moves.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = self.plan.return_to,
+            KeyCode::Enter if finished => self.mode = self.plan.return_to,
+            KeyCode::Char(' ')
+            | KeyCode::Enter
+            | KeyCode::Right
+            | KeyCode::Char('l')
+            | KeyCode::Char('n') => {
+                self.plan.playing = false;
+                self.plan_step_forward();
+            }
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') | KeyCode::Char('b') => {
+                self.plan.playing = false;
+                self.plan_step_back();
+            }
+            KeyCode::Char('a') | KeyCode::Char('A') => {
+                if finished {
+                    // Replay from the start
+                    let from = self.plan.plan.from.clone();
+                    self.apply_dipswitch(&from);
+                    self.plan.done = 0;
+                }
+                self.plan.playing = !self.plan.playing;
+                self.plan.idle = 0.0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Make the next move of the plan, focusing the switch after it
+    fn plan_step_forward(&mut self) {
+        let moves = &self.plan.plan.moves;
+        let Some(&next) = moves.get(self.plan.done) else {
+            return;
+        };
+        self.plan.done += 1;
+        self.focus = moves
+            .get(self.plan.done)
+            .map_or(next.switch, |after| after.switch);
+        self.set_switch(next.switch, next.right);
+        self.history.record(self.dipswitch.clone());
+    }
+
+    /// Follow the plan from a click on a switch: the switch of the next move
+    /// makes that move, the switch of the last move takes it back and any
+    /// other switch stays where it is
+    fn click_plan_switch(&mut self, index: usize) {
+        self.plan.playing = false;
+        let moves = &self.plan.plan.moves;
+        if moves
+            .get(self.plan.done)
+            .is_some_and(|next| next.switch == index)
+        {
+            self.plan_step_forward();
+        } else if self
+            .plan
+            .done
+            .checked_sub(1)
+            .is_some_and(|last| moves[last].switch == index)
+        {
+            self.plan_step_back();
+        }
+    }
+
+    /// Take back the last move of the plan
+    fn plan_step_back(&mut self) {
+        let Some(done) = self.plan.done.checked_sub(1) else {
+            return;
+        };
+        let last = self.plan.plan.moves[done];
+        self.plan.done = done;
+        self.focus = last.switch;
+        self.set_switch(last.switch, !last.right);
+        self.history.record(self.dipswitch.clone());
+    }
+
+    /// Setting of a compare bank: 0 = the unit's switches, 1 = required
+    pub fn bank(&self, bank: usize) -> &DipSwitch {
+        if bank == 0 {
+            &self.dipswitch
+        } else {
+            &self.compare.required
+        }
+    }
+
+    /// Move a switch of a compare bank, animating if it changes
+    fn set_bank_switch(&mut self, bank: usize, index: usize, state: bool) {
+        if bank == 0 {
+            self.set_switch(index, state);
+        } else {
+            self.compare.set(index, state);
+        }
+    }
+
+    /// Animate back to the previous setting in the history
+    fn undo(&mut self) {
+        if let Some(target) = self.history.undo().cloned() {
+            self.apply_dipswitch(&target);
+        }
+    }
+
+    /// Animate forward to the next undone setting in the history
+    fn redo(&mut self) {
+        if let Some(target) = self.history.redo().cloned() {
+            self.apply_dipswitch(&target);
+        }
+    }
+
+    /// Animate the format table switches into the cell under the cursor
+    fn apply_table_cell(&mut self) {
+        let entry = self
+            .profile
+            .table_grid_entry(self.table.row, self.table.column);
+        let mut target = self.dipswitch.clone();
+        self.profile.apply_table_entry(&mut target, entry);
+        self.apply_dipswitch(&target);
+    }
+
     /// Toggle a switch and start its animation
     fn toggle_switch(&mut self, index: usize) {
         if index < 8 {
@@ -107,6 +655,20 @@ impl App {
         }
     }
 
+    /// Move a switch to a position (true = RIGHT), animating if it changes
+    fn set_switch(&mut self, index: usize, state: bool) {
+        if self.dipswitch.get(index) != state {
+            self.toggle_switch(index);
+        }
+    }
+
+    /// Animate every switch that differs from `target` into position
+    fn apply_dipswitch(&mut self, target: &DipSwitch) {
+        for index in 0..8 {
+            self.set_switch(index, target.get(index));
+        }
+    }
+
     /// Reset all switches to default and animate them
     fn reset_switches(&mut self) {
         self.dipswitch.reset();
@@ -122,6 +684,311 @@ impl Default for App {
     }
 }
 
+/// State of the oscilloscope panel showing a group's reference waveform
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScopeView {
+    pub visible: bool,
+    /// Group whose output is traced (0 = group 1)
+    pub group: usize,
+}
+
+/// Cursor of the Table 2 browser, as a grid cell
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableView {
+    pub row: usize,
+    pub column: usize,
+}
+
+/// The "required" bank of the compare view, set independently of the unit's
+/// switches (the main bank)
+#[derive(Debug, Clone)]
+pub struct CompareView {
+    /// Bank receiving switch keys: 0 = current unit, 1 = required
+    pub bank: usize,
+    pub required: DipSwitch,
+    pub animations: [SwitchAnimation; 8],
+}
+
+impl Default for CompareView {
+    fn default() -> Self {
+        Self {
+            bank: 0,
+            required: DipSwitch::new(),
+            animations: [SwitchAnimation::new(false); 8],
+        }
+    }
+}
+
+impl CompareView {
+    /// Move a required switch, animating if it changes
+    fn set(&mut self, index: usize, state: bool) {
+        if index < 8 && self.required.get(index) != state {
+            self.required.toggle(index);
+            self.animations[index].start_animation(state);
+        }
+    }
+
+    /// Animate every required switch that differs from `target`
+    fn apply(&mut self, target: &DipSwitch) {
+        for index in 0..8 {
+            self.set(index, target.get(index));
+        }
+    }
+}
+
+/// Seconds between moves when a toggle plan plays itself
+const PLAN_STEP_PAUSE: f64 = 0.6;
+
+/// Playback of a toggle plan on the unit's switches
+#[derive(Debug, Clone, Default)]
+pub struct PlanView {
+    pub plan: TogglePlan,
+    /// Moves made so far
+    pub done: usize,
+    /// The remaining moves are being made automatically
+    pub playing: bool,
+    /// Seconds the switches have been still while playing
+    idle: f64,
+    /// Mode to go back to when the plan is closed
+    return_to: Mode,
+}
+
+/// Saved presets and the state of the preset picker
+#[derive(Debug, Clone, Default)]
+pub struct PresetView {
+    pub store: PresetStore,
+    /// File the presets are saved to; `None` if there is no config directory
+    /// or the file could not be read
+    pub path: Option<PathBuf>,
+    pub selected: usize,
+    /// Name being typed for a new preset
+    pub naming: Option<String>,
+    /// Result of the last save or removal
+    pub message: Option<String>,
+}
+
+impl PresetView {
+    pub fn new(store: PresetStore, path: Option<PathBuf>) -> Self {
+        Self {
+            store,
+            path,
+            ..Self::default()
+        }
+    }
+
+    /// Save `dipswitch` under `name` and write the preset file
+    fn save_as(&mut self, name: &str, dipswitch: &DipSwitch) {
+        let mut store = self.store.clone();
+        let message = match store.insert(name, dipswitch.clone()) {
+            Ok(replaced) => match self.write(&store) {
+                Ok(()) => {
+                    self.store = store;
+                    if let Some(index) = self
+                        .store
+                        .presets()
+                        .iter()
+                        .position(|p| p.name == name.trim())
+                    {
+                        self.selected = index;
+                    }
+                    format!(
+                        "{} '{}'",
+                        if replaced { "Replaced" } else { "Saved" },
+                        name.trim()
+                    )
+                }
+                Err(message) => message,
+            },
+            Err(err) => err.to_string(),
+        };
+        self.message = Some(message);
+    }
+
+    /// Remove the selected preset and write the preset file
+    fn remove_selected(&mut self) {
+        let Some(preset) = self.store.presets().get(self.selected) else {
+            return;
+        };
+        let name = preset.name.clone();
+        let mut store = self.store.clone();
+        store.remove(&name);
+        self.message = Some(match self.write(&store) {
+            Ok(()) => {
+                self.store = store;
+                self.selected = self
+                    .selected
+                    .min(self.store.presets().len().saturating_sub(1));
+                format!("Removed '{}'", name)
+            }
+            Err(message) => message,
+        });
+    }
+
+    fn write(&self, store: &PresetStore) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or("no preset file to save presets in")?;
+        store
+            .save(path)
+            .map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
+
+/// State of the format search popup
+#[derive(Debug, Clone, Default)]
+pub struct SearchView {
+    pub query: String,
+    pub matches: Vec<FormatMatch>,
+    pub selected: usize,
+    /// Group the chosen format is applied to (0 = group 1)
+    pub group: usize,
+}
+
+impl SearchView {
+    /// Open with an empty query, listing every format
+    fn open(&mut self, profile: &Profile) {
+        self.query.clear();
+        self.update(profile);
+    }
+
+    fn update(&mut self, profile: &Profile) {
+        self.matches = profile.search_formats(&self.query);
+        self.selected = 0;
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if !self.matches.is_empty() {
+            let len = self.matches.len() as isize;
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    pub fn selected_match(&self) -> Option<&FormatMatch> {
+        self.matches.get(self.selected)
+    }
+
+    /// Setting that puts the selected group on a matched format: its sync
+    /// switch, plus the SD format switch or the table entry needing the
+    /// fewest switch moves from `current`
+    pub fn setting_for(
+        &self,
+        profile: &Profile,
+        current: &DipSwitch,
+        found: &FormatMatch,
+    ) -> DipSwitch {
+        let mut target = current.clone();
+        target.set(profile.group_sync[self.group], found.format.is_hd());
+        if found.format.is_hd() {
+            let moves = |setting: &DipSwitch| (setting.to_raw() ^ current.to_raw()).count_ones();
+            let settings = found.entries.iter().map(|&entry| {
+                let mut setting = target.clone();
+                profile.apply_table_entry(&mut setting, entry);
+                setting
+            });
+            if let Some(best) = settings.min_by_key(moves) {
+                target = best;
+            }
+        } else if let Some(index) = profile
+            .sd_formats
+            .iter()
+            .position(|sd| sd.same_signal(&found.format))
+        {
+            target.set(profile.sd_format, index == 1);
+        }
+        target
+    }
+}
+
+/// Focusable column in the reverse lookup popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverFocus {
+    Group1,
+    Group2,
+    Results,
+}
+
+impl SolverFocus {
+    fn next(self) -> Self {
+        match self {
+            SolverFocus::Group1 => SolverFocus::Group2,
+            SolverFocus::Group2 => SolverFocus::Results,
+            SolverFocus::Results => SolverFocus::Group1,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            SolverFocus::Group1 => SolverFocus::Results,
+            SolverFocus::Group2 => SolverFocus::Group1,
+            SolverFocus::Results => SolverFocus::Group2,
+        }
+    }
+}
+
+/// State of the reverse lookup popup (desired formats → switch settings)
+pub struct SolverView {
+    pub targets: Vec<GroupTarget>,
+    pub group1: usize,
+    pub group2: usize,
+    pub focus: SolverFocus,
+    pub solutions: Vec<Solution>,
+    pub selected: usize,
+    target: ConfigTarget,
+}
+
+impl SolverView {
+    fn new(profile: &Profile) -> Self {
+        let targets = solver::available_targets_with(profile);
+        let target = ConfigTarget {
+            group1: targets[0],
+            group2: targets[0],
+            sd_video: None,
+        };
+        Self {
+            targets,
+            group1: 0,
+            group2: 0,
+            focus: SolverFocus::Group1,
+            solutions: Vec::new(),
+            selected: 0,
+            target,
+        }
+    }
+
+    /// Open the popup, keeping the SD output of the current setting
+    fn open(&mut self, profile: &Profile, dipswitch: &DipSwitch) {
+        self.target.sd_video = Some(profile.config(dipswitch).sd_video);
+        self.focus = SolverFocus::Group1;
+        self.recalculate(profile);
+    }
+
+    fn move_selection(&mut self, profile: &Profile, delta: isize) {
+        let (index, len) = match self.focus {
+            SolverFocus::Group1 => (&mut self.group1, self.targets.len()),
+            SolverFocus::Group2 => (&mut self.group2, self.targets.len()),
+            SolverFocus::Results => (&mut self.selected, self.solutions.len()),
+        };
+        if len > 0 {
+            *index = (*index as isize + delta).rem_euclid(len as isize) as usize;
+        }
+        if self.focus != SolverFocus::Results {
+            self.recalculate(profile);
+        }
+    }
+
+    fn recalculate(&mut self, profile: &Profile) {
+        self.target.group1 = self.targets[self.group1];
+        self.target.group2 = self.targets[self.group2];
+        self.solutions = solver::solve_with(profile, &self.target);
+        self.selected = 0;
+    }
+
+    fn selected_solution(&self) -> Option<&Solution> {
+        self.solutions.get(self.selected)
+    }
+}
+
 /// Animation state for a single DIP switch
 #[derive(Debug, Clone, Copy)]
 pub struct SwitchAnimation {
@@ -160,10 +1027,9 @@ impl SwitchAnimation {
         self.current_position += direction * Self::ANIMATION_SPEED * delta_time;
 
         // Clamp and check if animation complete
-        if direction > 0.0 && self.current_position >= target {
-            self.current_position = target;
-            self.animating = false;
-        } else if direction < 0.0 && self.current_position <= target {
+        if (direction > 0.0 && self.current_position >= target)
+            || (direction < 0.0 && self.current_position <= target)
+        {
             self.current_position = target;
             self.animating = false;
         }
@@ -174,3 +1040,209 @@ impl SwitchAnimation {
         self.current_position
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::layout::Rect;
+
+    use super::*;
+
+    /// Draw the app into a test terminal and keep its hit map, as `run` does
+    fn render(app: &mut App, width: u16, height: u16) {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut hits = HitMap::default();
+        terminal
+            .draw(|frame| hits = ui::render(frame, app))
+            .unwrap();
+        app.hits = hits;
+    }
+
+    /// Region of the last frame that hits `target`
+    fn area_of(app: &App, target: HitTarget) -> Rect {
+        (0..200)
+            .flat_map(|row| (0..300).map(move |column| (column, row)))
+            .find_map(|(column, row)| app.hits.hit(column, row).filter(|&(_, hit)| hit == target))
+            .map(|(area, _)| area)
+            .unwrap_or_else(|| panic!("{:?} not drawn", target))
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    fn click(app: &mut App, target: HitTarget) {
+        let area = area_of(app, target);
+        app.handle_mouse_event(mouse(
+            MouseEventKind::Down(MouseButton::Left),
+            area.x,
+            area.y,
+        ));
+        app.handle_mouse_event(mouse(MouseEventKind::Up(MouseButton::Left), area.x, area.y));
+    }
+
+    /// Press the button off the switches and drag it over the right half of
+    /// every switch
+    fn drag_right(app: &mut App) {
+        app.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 0, 0));
+        for switch in 0..app.profile.switch_count() {
+            let area = area_of(app, HitTarget::Switch(switch));
+            let kind = MouseEventKind::Drag(MouseButton::Left);
+            app.handle_mouse_event(mouse(kind, area.right() - 1, area.y));
+        }
+        app.handle_mouse_event(mouse(MouseEventKind::Up(MouseButton::Left), 0, 0));
+    }
+
+    fn key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+        app.handle_event(Event::Key(KeyEvent::new(code, modifiers)));
+    }
+
+    #[test]
+    fn test_one_undo_step_per_event() {
+        let mut app = App::new();
+        key(&mut app, KeyCode::Char('1'), KeyModifiers::NONE);
+        key(&mut app, KeyCode::Char('3'), KeyModifiers::NONE);
+        assert_eq!(app.history.states().len(), 3);
+        assert_eq!(app.history.position(), 2);
+
+        // Events that leave the switches alone add no step
+        key(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        key(&mut app, KeyCode::Char('s'), KeyModifiers::NONE);
+        assert_eq!(app.history.states().len(), 3);
+
+        // Reset moves two switches in one step
+        key(&mut app, KeyCode::Char('r'), KeyModifiers::NONE);
+        assert_eq!(app.history.states().len(), 4);
+        assert_eq!(app.dipswitch, DipSwitch::new());
+    }
+
+    #[test]
+    fn test_undo_redo_keep_history() {
+        let mut app = App::new();
+        key(&mut app, KeyCode::Char('1'), KeyModifiers::NONE);
+        key(&mut app, KeyCode::Char('2'), KeyModifiers::NONE);
+
+        key(&mut app, KeyCode::Char('u'), KeyModifiers::NONE);
+        assert_eq!(app.history.states().len(), 3);
+        assert_eq!(app.history.position(), 1);
+        assert_eq!(app.dipswitch, "RLLLLLLL".parse().unwrap());
+
+        key(&mut app, KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!(app.history.states().len(), 3);
+        assert_eq!(app.history.position(), 2);
+        assert_eq!(app.dipswitch, "RRLLLLLL".parse().unwrap());
+
+        // A new change after an undo drops the undone step
+        key(&mut app, KeyCode::Char('u'), KeyModifiers::NONE);
+        key(&mut app, KeyCode::Char('8'), KeyModifiers::NONE);
+        assert_eq!(app.history.states().len(), 3);
+        assert_eq!(
+            app.history.current(),
+            &"RLLLLLLR".parse::<DipSwitch>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_click_toggles_switch() {
+        let mut app = App::new();
+        render(&mut app, 100, 40);
+        click(&mut app, HitTarget::Switch(3));
+        assert!(app.dipswitch.get(3));
+        assert_eq!(app.focus, 3);
+        click(&mut app, HitTarget::Switch(3));
+        assert!(!app.dipswitch.get(3));
+    }
+
+    #[test]
+    fn test_drag_sets_switches() {
+        let mut app = App::new();
+        render(&mut app, 100, 40);
+        drag_right(&mut app);
+        assert_eq!(app.dipswitch, "RRRRRRRR".parse().unwrap());
+
+        // Moving back to the left half sets the switch LEFT
+        let area = area_of(&app, HitTarget::Switch(4));
+        app.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 0, 0));
+        app.handle_mouse_event(mouse(
+            MouseEventKind::Drag(MouseButton::Left),
+            area.x,
+            area.y,
+        ));
+        assert!(!app.dipswitch.get(4));
+    }
+
+    #[test]
+    fn test_drag_within_clicked_cell_does_not_toggle_again() {
+        let mut app = App::new();
+        render(&mut app, 100, 40);
+        let area = area_of(&app, HitTarget::Switch(0));
+        app.handle_mouse_event(mouse(
+            MouseEventKind::Down(MouseButton::Left),
+            area.x,
+            area.y,
+        ));
+        app.handle_mouse_event(mouse(
+            MouseEventKind::Drag(MouseButton::Left),
+            area.x,
+            area.y,
+        ));
+        assert!(app.dipswitch.get(0));
+    }
+
+    #[test]
+    fn test_click_key_hint() {
+        let mut app = App::new();
+        render(&mut app, 100, 40);
+        click(&mut app, HitTarget::Key(KeyCode::Char('q')));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_plan_clicks_follow_the_plan() {
+        let mut app = App::new();
+        let to: DipSwitch = "RLRLLLLL".parse().unwrap();
+        let plan = TogglePlan::between(&app.profile, &app.dipswitch, &to);
+        app.start_plan(plan, Mode::Normal);
+        render(&mut app, 100, 40);
+
+        // Switch 2 is not part of the plan and stays put
+        click(&mut app, HitTarget::Switch(1));
+        assert_eq!(app.plan.done, 0);
+        assert_eq!(app.dipswitch, DipSwitch::new());
+
+        // Switch 3 is the second move, not the next one
+        click(&mut app, HitTarget::Switch(2));
+        assert_eq!(app.plan.done, 0);
+
+        click(&mut app, HitTarget::Switch(0));
+        assert_eq!(app.plan.done, 1);
+        assert!(app.dipswitch.get(0));
+        click(&mut app, HitTarget::Switch(2));
+        assert_eq!(app.plan.done, 2);
+        assert_eq!(app.dipswitch, to);
+
+        // Clicking the last moved switch takes its move back
+        click(&mut app, HitTarget::Switch(2));
+        assert_eq!(app.plan.done, 1);
+        assert!(!app.dipswitch.get(2));
+    }
+
+    #[test]
+    fn test_plan_ignores_drags() {
+        let mut app = App::new();
+        let to: DipSwitch = "RLLLLLLL".parse().unwrap();
+        let plan = TogglePlan::between(&app.profile, &app.dipswitch, &to);
+        app.start_plan(plan, Mode::Normal);
+        render(&mut app, 100, 40);
+
+        drag_right(&mut app);
+        assert_eq!(app.plan.done, 0);
+        assert_eq!(app.dipswitch, DipSwitch::new());
+    }
+}
@@ -0,0 +1,322 @@
+use crate::cadence::CadenceAnalysis;
+use crate::dipswitch::{AudioOutput, OutputConfig};
+use crate::timing::gcd;
+use crate::video_format::{FrameRate, VideoFormat};
+
+/// Sample rate of the AES-11 digital audio reference (DARS)
+pub const AES_SAMPLE_RATE: u32 = 48_000;
+
+/// Frequency of the line-up tone
+pub const TONE_FREQUENCY_HZ: u32 = 1_000;
+
+/// Level of the line-up tone (SMPTE RP 155 reference level)
+pub const TONE_LEVEL_DBFS: i32 = -20;
+
+/// Line-up tone carried on the AES-11 output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tone {
+    pub frequency_hz: u32,
+    pub level_dbfs: i32,
+}
+
+/// How the audio reference relates to one output group's video
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupAudio {
+    /// Audio samples in each video frame of one sequence, e.g. 1602, 1601,
+    /// 1602, 1601, 1602 at 29.97
+    pub samples_per_frame: Vec<u32>,
+    /// The AES-11 frame timing repeats with this group's video frames, so
+    /// the sequence stays locked to them
+    pub frame_locked: bool,
+}
+
+impl GroupAudio {
+    /// "1920" or "1602/1601/1602/1601/1602"
+    pub fn cadence_name(&self) -> String {
+        let samples: Vec<String> = self.samples_per_frame.iter().map(u32::to_string).collect();
+        samples.join("/")
+    }
+}
+
+/// AES-11 digital audio reference output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioReference {
+    pub sample_rate: u32,
+    /// Tone on the output, `None` when silent
+    pub tone: Option<Tone>,
+    /// Video reference the audio is locked to (the SD house reference)
+    pub reference: VideoFormat,
+    /// Relation to the video of group 1 and group 2
+    pub groups: [GroupAudio; 2],
+}
+
+impl AudioReference {
+    /// Samples per frame over one sequence, starting at a frame where audio
+    /// and video align; each frame gets the rounded share of the sequence
+    pub fn samples_per_frame(rate: FrameRate) -> Vec<u32> {
+        // Samples per frame = sample rate * den / num; the sequence repeats
+        // after num / gcd(sample rate * den, num) frames
+        let samples = AES_SAMPLE_RATE as u64 * rate.denominator() as u64;
+        let frames = rate.numerator() as u64;
+        let sequence = frames / gcd(samples, frames);
+        let boundary = |k: u64| (2 * k * samples + frames) / (2 * frames);
+        (0..sequence)
+            .map(|k| (boundary(k + 1) - boundary(k)) as u32)
+            .collect()
+    }
+
+    /// Samples in a clip of at least `seconds`, rounded up as `clip` does
+    pub fn clip_len(&self, frame_rate: FrameRate, seconds: f64) -> u64 {
+        let sequence: u64 = AudioReference::samples_per_frame(frame_rate)
+            .iter()
+            .map(|&n| n as u64)
+            .sum();
+        let requested = (seconds.max(0.0) * self.sample_rate as f64).ceil() as u64;
+        requested.div_ceil(sequence).max(1).saturating_mul(sequence)
+    }
+
+    /// Render at least `seconds` of the output, rounded up to whole sample
+    /// sequences of `frame_rate` (e.g. multiples of 8008 samples at 29.97)
+    pub fn clip(&self, frame_rate: FrameRate, seconds: f64) -> AudioClip {
+        let samples_per_frame = AudioReference::samples_per_frame(frame_rate);
+        let sequence: u64 = samples_per_frame.iter().map(|&n| n as u64).sum();
+        let length = self.clip_len(frame_rate, seconds) as usize;
+        let sequences = length as u64 / sequence;
+
+        let samples = match self.tone {
+            Some(tone) => {
+                let amplitude = 10f64.powf(tone.level_dbfs as f64 / 20.0) * 8_388_607.0;
+                let step =
+                    std::f64::consts::TAU * tone.frequency_hz as f64 / self.sample_rate as f64;
+                (0..length)
+                    .map(|n| (amplitude * (step * n as f64).sin()).round() as i32)
+                    .collect()
+            }
+            None => vec![0; length],
+        };
+
+        AudioClip {
+            sample_rate: self.sample_rate,
+            frame_rate,
+            frames: sequences as usize * samples_per_frame.len(),
+            samples_per_frame,
+            samples,
+        }
+    }
+}
+
+/// Audio reference rendered as PCM, a whole number of sample sequences long
+/// so every video frame boundary falls on a known sample count
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioClip {
+    pub sample_rate: u32,
+    /// Frame rate the clip is aligned to
+    pub frame_rate: FrameRate,
+    /// Samples in each video frame of one sequence
+    pub samples_per_frame: Vec<u32>,
+    /// Video frames covered by the clip
+    pub frames: usize,
+    /// 24-bit samples, the same on both channels of the AES pair
+    pub samples: Vec<i32>,
+}
+
+impl AudioClip {
+    /// Channels in the clip (one AES pair)
+    pub const CHANNELS: u16 = 2;
+
+    /// Length in seconds
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate as f64
+    }
+
+    /// Sample index at which each video frame starts
+    pub fn frame_starts(&self) -> impl Iterator<Item = usize> + '_ {
+        self.samples_per_frame
+            .iter()
+            .cycle()
+            .take(self.frames)
+            .scan(0usize, |start, &samples| {
+                let frame_start = *start;
+                *start += samples as usize;
+                Some(frame_start)
+            })
+    }
+
+    /// Longest clip whose WAV data chunk fits the 32-bit RIFF chunk size
+    pub const MAX_WAV_SAMPLES: u64 = (u32::MAX as u64 - 36) / (Self::CHANNELS as u64 * 3);
+
+    /// 24-bit stereo PCM WAV file, `None` if longer than `MAX_WAV_SAMPLES`
+    pub fn to_wav(&self) -> Option<Vec<u8>> {
+        let block_align = Self::CHANNELS * 3;
+        let data_len = u32::try_from(self.samples.len())
+            .ok()
+            .and_then(|samples| samples.checked_mul(block_align as u32))
+            .filter(|&data_len| data_len <= u32::MAX - 36)?;
+
+        let mut out = Vec::with_capacity(44 + data_len as usize);
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data_len).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        out.extend_from_slice(&Self::CHANNELS.to_le_bytes());
+        out.extend_from_slice(&self.sample_rate.to_le_bytes());
+        out.extend_from_slice(&(self.sample_rate * block_align as u32).to_le_bytes());
+        out.extend_from_slice(&block_align.to_le_bytes());
+        out.extend_from_slice(&24u16.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &self.samples {
+            for _ in 0..Self::CHANNELS {
+                out.extend_from_slice(&sample.to_le_bytes()[..3]);
+            }
+        }
+        Some(out)
+    }
+}
+
+impl OutputConfig {
+    /// Sample rate, tone and per-group frame cadence of the AES-11 output
+    ///
+    /// The audio reference is locked to the SD house reference, so a group at
+    /// an integer rate beside 525 (or an x/1.001 rate beside 625) is not
+    /// frame-locked to it.
+    pub fn audio_reference(&self) -> AudioReference {
+        let group = |format: &VideoFormat| GroupAudio {
+            samples_per_frame: AudioReference::samples_per_frame(format.frame_rate),
+            frame_locked: CadenceAnalysis::between(format, &self.sd_format)
+                .warnings
+                .is_empty(),
+        };
+
+        AudioReference {
+            sample_rate: AES_SAMPLE_RATE,
+            tone: match self.audio {
+                AudioOutput::Tone => Some(Tone {
+                    frequency_hz: TONE_FREQUENCY_HZ,
+                    level_dbfs: TONE_LEVEL_DBFS,
+                }),
+                AudioOutput::Silent => None,
+            },
+            reference: self.sd_format,
+            groups: [group(&self.group1_format), group(&self.group2_format)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::Profile;
+
+    #[test]
+    fn test_samples_per_frame() {
+        assert_eq!(
+            AudioReference::samples_per_frame(FrameRate::FPS_25),
+            vec![1920]
+        );
+        assert_eq!(
+            AudioReference::samples_per_frame(FrameRate::FPS_23_98),
+            vec![2002]
+        );
+        assert_eq!(
+            AudioReference::samples_per_frame(FrameRate::FPS_30),
+            vec![1600]
+        );
+        assert_eq!(
+            AudioReference::samples_per_frame(FrameRate::FPS_29_97),
+            vec![1602, 1601, 1602, 1601, 1602]
+        );
+        let sequence = AudioReference::samples_per_frame(FrameRate::FPS_59_94);
+        assert_eq!(sequence.len(), 5);
+        assert_eq!(sequence.iter().sum::<u32>(), 4004);
+    }
+
+    #[test]
+    fn test_tone() {
+        let profile = Profile::gen10();
+        let silent = profile
+            .config(&"LLLLLLLL".parse().unwrap())
+            .audio_reference();
+        assert_eq!(silent.tone, None);
+        assert_eq!(silent.sample_rate, 48_000);
+
+        let tone = profile
+            .config(&"LLRLLLLL".parse().unwrap())
+            .audio_reference();
+        assert_eq!(
+            tone.tone,
+            Some(Tone {
+                frequency_hz: 1000,
+                level_dbfs: -20
+            })
+        );
+    }
+
+    #[test]
+    fn test_clip_aligned_to_sequence() {
+        let audio = Profile::gen10()
+            .config(&"LLRLLLLL".parse().unwrap())
+            .audio_reference();
+        let clip = audio.clip(FrameRate::FPS_29_97, 10.0);
+        assert_eq!(audio.clip_len(FrameRate::FPS_29_97, 10.0), 60 * 8008);
+        // 10 s rounds up to 60 sequences of 8008 samples (300 frames)
+        assert_eq!(clip.samples.len(), 60 * 8008);
+        assert_eq!(clip.frames, 300);
+        let starts: Vec<usize> = clip.frame_starts().take(6).collect();
+        assert_eq!(starts, vec![0, 1602, 3203, 4805, 6406, 8008]);
+
+        // -20 dBFS peak is a tenth of full scale
+        let peak = clip.samples.iter().map(|s| s.abs()).max().unwrap();
+        assert_eq!(peak, 838_861);
+        // 1 kHz at 48 kHz: 48 samples per cycle
+        assert_eq!(clip.samples[0], 0);
+        assert_eq!(clip.samples[12], 838_861);
+    }
+
+    #[test]
+    fn test_silent_wav() {
+        let audio = Profile::gen10()
+            .config(&"LLLLLLLL".parse().unwrap())
+            .audio_reference();
+        let clip = audio.clip(FrameRate::FPS_25, 0.04);
+        assert_eq!(clip.samples, vec![0; 1920]);
+
+        let wav = clip.to_wav().unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([wav[34], wav[35]]), 24);
+        assert_eq!(wav.len(), 44 + 1920 * 6);
+    }
+
+    #[test]
+    fn test_wav_length_limit() {
+        let audio = Profile::gen10()
+            .config(&"LLLLLLLL".parse().unwrap())
+            .audio_reference();
+        // Just over four hours fills the 32-bit data chunk
+        assert!(audio.clip_len(FrameRate::FPS_25, 4.0 * 3600.0) <= AudioClip::MAX_WAV_SAMPLES);
+        assert!(audio.clip_len(FrameRate::FPS_25, 5.0 * 3600.0) > AudioClip::MAX_WAV_SAMPLES);
+        assert!(audio.clip_len(FrameRate::FPS_25, 1e300) > AudioClip::MAX_WAV_SAMPLES);
+    }
+
+    #[test]
+    fn test_frame_lock_follows_house_reference() {
+        let profile = Profile::gen10();
+        // Group 1 1080i60 beside the 525 house reference, group 2 SD
+        let audio = profile
+            .config(&"RLLLLLLR".parse().unwrap())
+            .audio_reference();
+        assert_eq!(audio.reference, VideoFormat::sd_525());
+        assert!(!audio.groups[0].frame_locked);
+        assert!(audio.groups[1].frame_locked);
+        assert_eq!(audio.groups[0].cadence_name(), "1600");
+        assert_eq!(audio.groups[1].cadence_name(), "1602/1601/1602/1601/1602");
+
+        // 1080p23.98 has a 2:3 relation to 525 and stays locked
+        let audio = profile
+            .config(&"RLLLLLRL".parse().unwrap())
+            .audio_reference();
+        assert!(audio.groups[0].frame_locked);
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+
+use crate::dipswitch::OutputConfig;
+use crate::timing::{Rational, gcd};
+use crate::video_format::{FrameRate, ScanType, VideoFormat};
+
+/// Longest alignment period (in seconds) that is still a usable cadence
+const MAX_CYCLE_SECONDS: u64 = 1;
+
+/// Longest pulldown sequence, in source frames, worth naming
+const MAX_PULLDOWN_FRAMES: u64 = 5;
+
+/// Repeat pattern used to carry the lower-rate group's frames at the other
+/// group's rate (e.g. 2:3 for 23.98 frames in 59.94 fields)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pulldown {
+    /// Group (0 = group 1) whose frames are repeated
+    pub source_group: usize,
+    /// Fields (or frames) of the other group per source frame, one cycle
+    pub pattern: Vec<u64>,
+    /// The pattern counts fields (or PsF segments) rather than frames
+    pub fields: bool,
+}
+
+impl fmt::Display for Pulldown {
+    /// "2:3 pulldown", "2:2 pulldown" or "1:1:1:2 frame repeat"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pattern: Vec<String> = self.pattern.iter().map(u64::to_string).collect();
+        if pattern.len() == 1 {
+            pattern.push(pattern[0].clone());
+        }
+        let kind = if self.fields {
+            "pulldown"
+        } else {
+            "frame repeat"
+        };
+        write!(f, "{} {}", pattern.join(":"), kind)
+    }
+}
+
+/// Reason a group mix is unusual for genlocking downstream equipment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CadenceWarning {
+    /// One group runs at an x/1.001 rate and the other at an integer rate
+    Drift { period: Rational, frames: [u64; 2] },
+    /// Frame boundaries only coincide after a long cycle
+    LongCycle { period: Rational, frames: [u64; 2] },
+}
+
+impl fmt::Display for CadenceWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CadenceWarning::Drift { period, frames } => write!(
+                f,
+                "1000/1001 rate mismatch: frames line up only every {:.2} s ({} and {} frames); downstream gear will drift",
+                period.as_f64(),
+                frames[0],
+                frames[1]
+            ),
+            CadenceWarning::LongCycle { period, frames } => write!(
+                f,
+                "frames line up only every {:.2} s ({} and {} frames); no usable cadence between the groups",
+                period.as_f64(),
+                frames[0],
+                frames[1]
+            ),
+        }
+    }
+}
+
+/// How the frame timing of the two output groups relates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CadenceAnalysis {
+    /// Every frame boundary of one group is also a frame boundary of the other
+    pub frames_aligned: bool,
+    /// Shortest time after which both groups start a frame together (seconds)
+    pub alignment_period: Rational,
+    /// Frames of group 1 and group 2 in one alignment period
+    pub alignment_frames: [u64; 2],
+    pub pulldown: Option<Pulldown>,
+    pub warnings: Vec<CadenceWarning>,
+}
+
+impl CadenceAnalysis {
+    /// Compare the frame timing of two group formats
+    pub fn between(group1: &VideoFormat, group2: &VideoFormat) -> CadenceAnalysis {
+        let rates = [group1.frame_rate, group2.frame_rate];
+        let periods = rates.map(|rate| (rate.denominator() as u64, rate.numerator() as u64));
+
+        // LCM of the frame periods a/b and c/d (both reduced) is lcm(a, c) / gcd(b, d)
+        let (a, b) = periods[0];
+        let (c, d) = periods[1];
+        let alignment_period = Rational::new(a / gcd(a, c) * c, gcd(b, d));
+        let alignment_frames = periods.map(|(num, den)| {
+            alignment_period.numerator() * den / (alignment_period.denominator() * num)
+        });
+
+        let frames_aligned = alignment_frames.contains(&1);
+        let mut warnings = Vec::new();
+        if is_drop_rate(rates[0]) != is_drop_rate(rates[1]) {
+            warnings.push(CadenceWarning::Drift {
+                period: alignment_period,
+                frames: alignment_frames,
+            });
+        } else if alignment_period.numerator() > MAX_CYCLE_SECONDS * alignment_period.denominator()
+        {
+            warnings.push(CadenceWarning::LongCycle {
+                period: alignment_period,
+                frames: alignment_frames,
+            });
+        }
+
+        CadenceAnalysis {
+            frames_aligned,
+            alignment_period,
+            alignment_frames,
+            pulldown: pulldown([group1, group2]),
+            warnings,
+        }
+    }
+
+    /// One-line description of the alignment, e.g. "every 5 frames of group 1
+    /// and 4 frames of group 2 (1001/6000 s)"
+    pub fn alignment_summary(&self) -> String {
+        if self.alignment_frames == [1, 1] {
+            return "every frame (same frame rate)".to_string();
+        }
+        format!(
+            "every {} frame{} of group 1 and {} frame{} of group 2 ({} s)",
+            self.alignment_frames[0],
+            if self.alignment_frames[0] == 1 {
+                ""
+            } else {
+                "s"
+            },
+            self.alignment_frames[1],
+            if self.alignment_frames[1] == 1 {
+                ""
+            } else {
+                "s"
+            },
+            self.alignment_period
+        )
+    }
+}
+
+impl OutputConfig {
+    /// Frame alignment, pulldown and genlock warnings between the two groups
+    pub fn cadence(&self) -> CadenceAnalysis {
+        CadenceAnalysis::between(&self.group1_format, &self.group2_format)
+    }
+}
+
+/// Rate belongs to the NTSC-related x/1.001 family
+fn is_drop_rate(rate: FrameRate) -> bool {
+    rate.denominator().is_multiple_of(1001)
+}
+
+/// Pulldown carrying the frame-based group's pictures in the other group
+fn pulldown(groups: [&VideoFormat; 2]) -> Option<Pulldown> {
+    let frame_based = |format: &VideoFormat| format.scan != ScanType::Interlaced;
+    // Source is the slower group; at equal rates, the frame-based one
+    let source_group = match groups[0]
+        .frame_rate
+        .as_f64()
+        .total_cmp(&groups[1].frame_rate.as_f64())
+    {
+        std::cmp::Ordering::Less => 0,
+        std::cmp::Ordering::Greater => 1,
+        std::cmp::Ordering::Equal => usize::from(!frame_based(groups[0])),
+    };
+    let source = groups[source_group];
+    let target = groups[1 - source_group];
+    if !frame_based(source) {
+        return None;
+    }
+
+    let fields = target.scan != ScanType::Progressive;
+    let target_rate = if fields {
+        target.frame_rate.times(2)?
+    } else {
+        target.frame_rate
+    };
+    // Target units per source frame
+    let ratio = Rational::new(
+        target_rate.numerator() as u64 * source.frame_rate.denominator() as u64,
+        target_rate.denominator() as u64 * source.frame_rate.numerator() as u64,
+    );
+    let (units, frames) = (ratio.numerator(), ratio.denominator());
+    if units <= frames || frames > MAX_PULLDOWN_FRAMES {
+        return None;
+    }
+
+    let pattern: Vec<u64> = (0..frames)
+        .map(|k| (k + 1) * units / frames - k * units / frames)
+        .collect();
+    // Shortest repeating unit: [2, 3, 2, 3] is 2:3
+    let cycle = (1..=pattern.len())
+        .find(|&len| {
+            pattern.len().is_multiple_of(len) && pattern.chunks(len).all(|c| c == &pattern[..len])
+        })
+        .unwrap_or(pattern.len());
+
+    Some(Pulldown {
+        source_group,
+        pattern: pattern[..cycle].to_vec(),
+        fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(name: &str) -> VideoFormat {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn test_same_rate_aligned() {
+        let analysis = CadenceAnalysis::between(&format("1080i59.94"), &format("525i"));
+        assert!(analysis.frames_aligned);
+        assert_eq!(analysis.alignment_frames, [1, 1]);
+        assert_eq!(analysis.pulldown, None);
+        assert!(analysis.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_23_98_into_525i() {
+        let analysis = CadenceAnalysis::between(&format("1080p23.98"), &format("525i"));
+        assert!(!analysis.frames_aligned);
+        assert_eq!(analysis.alignment_frames, [4, 5]);
+        assert_eq!(analysis.alignment_period, Rational::new(1001, 6000));
+        let pulldown = analysis.pulldown.unwrap();
+        assert_eq!(pulldown.source_group, 0);
+        assert_eq!(pulldown.to_string(), "2:3 pulldown");
+        assert!(analysis.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_progressive_into_interlaced_same_rate() {
+        let analysis = CadenceAnalysis::between(&format("1080i50"), &format("1080p25"));
+        assert!(analysis.frames_aligned);
+        let pulldown = analysis.pulldown.unwrap();
+        assert_eq!(pulldown.source_group, 1);
+        assert_eq!(pulldown.to_string(), "2:2 pulldown");
+    }
+
+    #[test]
+    fn test_frame_repeat() {
+        let analysis = CadenceAnalysis::between(&format("720p25"), &format("720p50"));
+        assert!(analysis.frames_aligned);
+        assert_eq!(analysis.alignment_frames, [1, 2]);
+        assert_eq!(analysis.pulldown.unwrap().to_string(), "2:2 frame repeat");
+    }
+
+    #[test]
+    fn test_drift_warning() {
+        let analysis = CadenceAnalysis::between(&format("1080p24"), &format("525i"));
+        assert_eq!(analysis.alignment_frames, [1001, 1250]);
+        assert!(matches!(
+            analysis.warnings[..],
+            [CadenceWarning::Drift { .. }]
+        ));
+        assert!(analysis.warnings[0].to_string().contains("41.71 s"));
+    }
+
+    #[test]
+    fn test_output_config_cadence() {
+        let config = crate::profile::Profile::gen10().config(&"RLLLLLRL".parse().unwrap());
+        let analysis = config.cadence();
+        assert_eq!(config.group1_format.name(), "1080p23.98");
+        assert_eq!(
+            analysis.alignment_summary(),
+            "every 4 frames of group 1 and 5 frames of group 2 (1001/6000 s)"
+        );
+    }
+}
@@ -0,0 +1,1478 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gen10ds::solver::{self, ConfigTarget, GroupTarget};
+use gen10ds::{
+    AudioClip, CadenceAnalysis, DipSwitch, FrameFileFormat, OutputConfig, PlanChoice, PresetStore,
+    Profile, SdFrame, SdVideo, TogglePlan, VideoFormat, Waveform, WaveformFileFormat, WaveformSpan,
+};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage:
+  gen10ds [--profile <FILE>] [--presets <FILE>] [COMMAND]
+  gen10ds                              Start the interactive simulator
+  gen10ds --preset <NAME>              Start the simulator with a saved preset
+  gen10ds --theme <NAME>               Start the simulator with a colour theme
+  gen10ds decode <POSITIONS> [--json]  Decode switch positions, e.g. LRRLLRLL
+  gen10ds decode --raw <VALUE> [--json]
+                                       Decode a raw 8-bit value (0x1B, 0b00011011, 27)
+  gen10ds encode --group1 <FORMAT> [--group2 <FORMAT>] [--bars]
+                                       Show the switch setting for a desired output
+  gen10ds frame <POSITIONS> --out <FILE> [--format <ppm|png|uyvy|v210>]
+  gen10ds frame --raw <VALUE> --out <FILE> [--format <ppm|png|uyvy|v210>]
+                                       Write the SD test frame for a switch setting
+  gen10ds audio [<POSITIONS> | --raw <VALUE>] --out <FILE> [--seconds <N>] [--group <1|2>]
+                                       Write the AES-11 tone or silence as a WAV file
+  gen10ds waveform <POSITIONS> --out <FILE> [--group <1|2>] [--span <line|field>]
+                   [--format <csv|f32>] [--oversample <N>]
+                                       Write a group's analog reference waveform
+  gen10ds plan <POSITIONS> --to <POSITIONS>
+  gen10ds plan <POSITIONS> --group1 <FORMAT> [--group2 <FORMAT>] [--bars | --black]
+               [--canonical]
+                                       Print the switch moves from one setting to another
+  gen10ds preset list                  List saved presets
+  gen10ds preset add <NAME> <POSITIONS>
+  gen10ds preset add <NAME> --raw <VALUE>
+                                       Save a switch setting as a preset
+  gen10ds preset remove <NAME>         Remove a preset
+  gen10ds help                         Show this message
+
+Formats are names such as 1080p25, 1080i59.94, 1080psf23.98, 720p50 or sd.
+Interlaced rates are field rates; 1080i25 is read as 1080i50 when the unit
+has no 25-field format.
+--group2 defaults to the --group1 format; --bars selects 75% Color Bars and
+tone instead of Color Black and silence.
+
+frame writes Color Black or 75% Color Bars (switch 3) in the 525 or 625
+standard (switch 4). The format defaults to the file extension; uyvy is 10-bit
+4:2:2 in little-endian 16-bit words and v210 is the packed 10-bit layout.
+
+audio writes 48 kHz 24-bit stereo WAV: a 1 kHz tone at -20 dBFS when switch 3
+is RIGHT, silence otherwise (default setting: all switches LEFT). The length
+(default 10 s) is rounded up to whole samples-per-frame sequences of the
+group's frame rate (--group, default 1) so frame boundaries fall on known
+sample counts. The WAV format limits the length to a little over four hours.
+
+waveform samples the analog reference for a group's format (--group, default
+1): NTSC or PAL blackburst for SD, tri-level sync for HD. --span line (the
+default) writes one active line, field the first field with its vertical
+interval (the whole frame for progressive formats). Samples are in mV at the
+format's sample clock times --oversample (default 4 for SD, 1 for HD); csv
+writes time_us,millivolts rows, f32 (.f32 or .raw) little-endian floats.
+
+Positions are listed switch 1 first, L = LEFT and R = RIGHT.
+In raw values bit 0 is switch 1 and bit 7 is switch 8.
+
+--profile loads a device profile (TOML) instead of the built-in GEN10; see
+profiles/gen10.toml for the format.
+
+plan lists the fewest switch moves from the first setting (positions or --raw
+<VALUE>) to the --to setting, or to a setting producing the given formats. For
+formats it picks, among the equivalent Table 2 settings, the one needing the
+fewest moves; --canonical uses the setting encode recommends instead. The SD
+output is left as it is unless --bars or --black is given.
+
+Presets are kept in $XDG_CONFIG_HOME/gen10ds/presets.toml (by default
+~/.config/gen10ds/presets.toml); --presets uses another file. Names are
+matched ignoring case, and adding an existing name replaces its setting.
+
+--theme picks the simulator's colours: default, high-contrast, colorblind
+(blue/orange, safe for red/green colour blindness), monochrome, or a theme
+defined in $XDG_CONFIG_HOME/gen10ds/themes.toml. All but default also mark
+switch state with text. Without --theme a non-empty NO_COLOR
+selects monochrome, otherwise the theme named in themes.toml is used.";
+
+/// Output style for non-interactive commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Parsed command line: global options and the command to run
+#[derive(Debug, Clone)]
+pub struct Cli {
+    pub profile: Option<PathBuf>,
+    /// Preset file to use instead of the one in the config directory
+    pub presets: Option<PathBuf>,
+    /// Preset to start the simulator with
+    pub preset: Option<String>,
+    /// Colour theme for the simulator
+    pub theme: Option<String>,
+    pub command: Command,
+}
+
+/// Switch setting as given on the command line, resolved against a profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwitchSetting {
+    Positions(String),
+    Raw(String),
+}
+
+impl SwitchSetting {
+    fn resolve(&self, profile: &Profile) -> Result<DipSwitch, String> {
+        match self {
+            SwitchSetting::Positions(positions) => {
+                profile.parse_switches(positions).map_err(|e| e.to_string())
+            }
+            SwitchSetting::Raw(value) => parse_raw(profile, value),
+        }
+    }
+}
+
+/// Command to run
+#[derive(Debug, Clone)]
+pub enum Command {
+    Tui,
+    Help,
+    Decode {
+        setting: SwitchSetting,
+        output: OutputFormat,
+    },
+    Encode {
+        target: ConfigTarget,
+    },
+    Frame {
+        setting: SwitchSetting,
+        out: PathBuf,
+        format: FrameFileFormat,
+    },
+    Audio {
+        setting: Option<SwitchSetting>,
+        out: PathBuf,
+        seconds: f64,
+        group: usize,
+    },
+    Waveform {
+        setting: SwitchSetting,
+        out: PathBuf,
+        group: usize,
+        span: WaveformSpan,
+        format: WaveformFileFormat,
+        oversample: Option<u32>,
+    },
+    Preset(PresetCommand),
+    Plan {
+        from: SwitchSetting,
+        target: PlanTarget,
+        choice: PlanChoice,
+    },
+}
+
+/// What a plan moves the switches to
+#[derive(Debug, Clone)]
+pub enum PlanTarget {
+    Setting(SwitchSetting),
+    Config(ConfigTarget),
+}
+
+/// Preset subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresetCommand {
+    List,
+    Add {
+        name: String,
+        setting: SwitchSetting,
+    },
+    Remove {
+        name: String,
+    },
+}
+
+/// Parse the command line arguments (without the program name)
+pub fn parse_args(args: &[String]) -> Result<Cli, String> {
+    let mut args = args;
+    let mut profile = None;
+    let mut presets = None;
+    let mut preset = None;
+    let mut theme = None;
+    while let Some((option, rest)) = args.split_first() {
+        let (value, rest) = match option.as_str() {
+            "--profile" => rest.split_first().ok_or("--profile requires a file")?,
+            "--presets" => rest.split_first().ok_or("--presets requires a file")?,
+            "--preset" => rest.split_first().ok_or("--preset requires a name")?,
+            "--theme" => rest.split_first().ok_or("--theme requires a name")?,
+            _ => break,
+        };
+        match option.as_str() {
+            "--profile" => profile = Some(PathBuf::from(value)),
+            "--presets" => presets = Some(PathBuf::from(value)),
+            "--preset" => preset = Some(value.clone()),
+            _ => theme = Some(value.clone()),
+        }
+        args = rest;
+    }
+
+    let command = match args.split_first() {
+        None => Command::Tui,
+        Some((command, rest)) => match command.as_str() {
+            "decode" => parse_decode(rest)?,
+            "encode" => parse_encode(rest)?,
+            "frame" => parse_frame(rest)?,
+            "audio" => parse_audio(rest)?,
+            "waveform" => parse_waveform(rest)?,
+            "preset" => parse_preset(rest)?,
+            "plan" => parse_plan(rest)?,
+            "help" | "--help" | "-h" => Command::Help,
+            other => return Err(format!("unknown command '{}'", other)),
+        },
+    };
+    if preset.is_some() && !matches!(command, Command::Tui) {
+        return Err("--preset only applies to the interactive simulator".to_string());
+    }
+    if theme.is_some() && !matches!(command, Command::Tui) {
+        return Err("--theme only applies to the interactive simulator".to_string());
+    }
+    Ok(Cli {
+        profile,
+        presets,
+        preset,
+        theme,
+        command,
+    })
+}
+
+fn parse_decode(args: &[String]) -> Result<Command, String> {
+    let mut output = OutputFormat::Text;
+    let mut setting = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--json" => {
+                output = OutputFormat::Json;
+                continue;
+            }
+            "--raw" => {
+                let value = args.next().ok_or("--raw requires a value")?;
+                SwitchSetting::Raw(value.clone())
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            positions => SwitchSetting::Positions(positions.to_string()),
+        };
+        if setting.replace(parsed).is_some() {
+            return Err("decode takes a single switch setting".to_string());
+        }
+    }
+
+    let setting = setting.ok_or("decode requires switch positions or --raw <VALUE>")?;
+    Ok(Command::Decode { setting, output })
+}
+
+fn parse_encode(args: &[String]) -> Result<Command, String> {
+    let mut group1 = None;
+    let mut group2 = None;
+    let mut sd_video = SdVideo::ColorBlack;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--group1" | "--group2" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{} requires a format", arg))?;
+                let target = value.parse::<GroupTarget>().map_err(|e| e.to_string())?;
+                if arg == "--group1" {
+                    group1 = Some(target);
+                } else {
+                    group2 = Some(target);
+                }
+            }
+            "--bars" => sd_video = SdVideo::ColorBars75,
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    let group1 = group1.ok_or("encode requires --group1 <FORMAT>")?;
+    let target = ConfigTarget {
+        group1,
+        group2: group2.unwrap_or(group1),
+        sd_video: Some(sd_video),
+    };
+    Ok(Command::Encode { target })
+}
+
+fn parse_frame(args: &[String]) -> Result<Command, String> {
+    let mut setting = None;
+    let mut out = None;
+    let mut format = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--out" => {
+                out = Some(PathBuf::from(args.next().ok_or("--out requires a file")?));
+                continue;
+            }
+            "--format" => {
+                let value = args.next().ok_or("--format requires a value")?;
+                format = Some(value.parse::<FrameFileFormat>()?);
+                continue;
+            }
+            "--raw" => {
+                let value = args.next().ok_or("--raw requires a value")?;
+                SwitchSetting::Raw(value.clone())
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            positions => SwitchSetting::Positions(positions.to_string()),
+        };
+        if setting.replace(parsed).is_some() {
+            return Err("frame takes a single switch setting".to_string());
+        }
+    }
+
+    let setting = setting.ok_or("frame requires switch positions or --raw <VALUE>")?;
+    let out = out.ok_or("frame requires --out <FILE>")?;
+    let format = match format {
+        Some(format) => format,
+        None => FrameFileFormat::from_path(&out).ok_or_else(|| {
+            format!(
+                "cannot tell the format of '{}'; use --format",
+                out.display()
+            )
+        })?,
+    };
+    Ok(Command::Frame {
+        setting,
+        out,
+        format,
+    })
+}
+
+fn parse_audio(args: &[String]) -> Result<Command, String> {
+    let mut setting = None;
+    let mut out = None;
+    let mut seconds = 10.0;
+    let mut group = 0;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--out" => {
+                out = Some(PathBuf::from(args.next().ok_or("--out requires a file")?));
+                continue;
+            }
+            "--seconds" => {
+                let value = args.next().ok_or("--seconds requires a value")?;
+                seconds = match value.parse::<f64>() {
+                    Ok(seconds) if seconds > 0.0 && seconds.is_finite() => seconds,
+                    _ => return Err(format!("invalid length '{}'", value)),
+                };
+                continue;
+            }
+            "--group" => {
+                group = match args.next().map(String::as_str) {
+                    Some("1") => 0,
+                    Some("2") => 1,
+                    _ => return Err("--group must be 1 or 2".to_string()),
+                };
+                continue;
+            }
+            "--raw" => {
+                let value = args.next().ok_or("--raw requires a value")?;
+                SwitchSetting::Raw(value.clone())
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            positions => SwitchSetting::Positions(positions.to_string()),
+        };
+        if setting.replace(parsed).is_some() {
+            return Err("audio takes a single switch setting".to_string());
+        }
+    }
+
+    let out = out.ok_or("audio requires --out <FILE>")?;
+    Ok(Command::Audio {
+        setting,
+        out,
+        seconds,
+        group,
+    })
+}
+
+fn parse_waveform(args: &[String]) -> Result<Command, String> {
+    let mut setting = None;
+    let mut out = None;
+    let mut group = 0;
+    let mut span = WaveformSpan::Line;
+    let mut format = None;
+    let mut oversample = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--out" => {
+                out = Some(PathBuf::from(args.next().ok_or("--out requires a file")?));
+                continue;
+            }
+            "--group" => {
+                group = match args.next().map(String::as_str) {
+                    Some("1") => 0,
+                    Some("2") => 1,
+                    _ => return Err("--group must be 1 or 2".to_string()),
+                };
+                continue;
+            }
+            "--span" => {
+                let value = args.next().ok_or("--span requires a value")?;
+                span = value.parse::<WaveformSpan>()?;
+                continue;
+            }
+            "--format" => {
+                let value = args.next().ok_or("--format requires a value")?;
+                format = Some(value.parse::<WaveformFileFormat>()?);
+                continue;
+            }
+            "--oversample" => {
+                let value = args.next().ok_or("--oversample requires a value")?;
+                oversample = match value.parse::<u32>() {
+                    Ok(factor @ 1..=16) => Some(factor),
+                    _ => {
+                        return Err(format!(
+                            "invalid oversampling '{}' (expected 1 to 16)",
+                            value
+                        ));
+                    }
+                };
+                continue;
+            }
+            "--raw" => {
+                let value = args.next().ok_or("--raw requires a value")?;
+                SwitchSetting::Raw(value.clone())
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            positions => SwitchSetting::Positions(positions.to_string()),
+        };
+        if setting.replace(parsed).is_some() {
+            return Err("waveform takes a single switch setting".to_string());
+        }
+    }
+
+    let setting = setting.ok_or("waveform requires switch positions or --raw <VALUE>")?;
+    let out = out.ok_or("waveform requires --out <FILE>")?;
+    let format = match format {
+        Some(format) => format,
+        None => WaveformFileFormat::from_path(&out).ok_or_else(|| {
+            format!(
+                "cannot tell the format of '{}'; use --format",
+                out.display()
+            )
+        })?,
+    };
+    Ok(Command::Waveform {
+        setting,
+        out,
+        group,
+        span,
+        format,
+        oversample,
+    })
+}
+
+fn parse_plan(args: &[String]) -> Result<Command, String> {
+    let mut from = None;
+    let mut to = None;
+    let mut group1 = None;
+    let mut group2 = None;
+    let mut sd_video = None;
+    let mut choice = PlanChoice::FewestMoves;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let setting = match arg.as_str() {
+            "--raw" => SwitchSetting::Raw(args.next().ok_or("--raw requires a value")?.clone()),
+            "--to" => {
+                let value = args.next().ok_or("--to requires switch positions")?;
+                to = Some(SwitchSetting::Positions(value.clone()));
+                continue;
+            }
+            "--group1" | "--group2" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{} requires a format", arg))?;
+                let target = value.parse::<GroupTarget>().map_err(|e| e.to_string())?;
+                if arg == "--group1" {
+                    group1 = Some(target);
+                } else {
+                    group2 = Some(target);
+                }
+                continue;
+            }
+            "--bars" | "--black" => {
+                let video = if arg == "--bars" {
+                    SdVideo::ColorBars75
+                } else {
+                    SdVideo::ColorBlack
+                };
+                if sd_video
+                    .replace(video)
+                    .is_some_and(|previous| previous != video)
+                {
+                    return Err("--bars and --black cannot be combined".to_string());
+                }
+                continue;
+            }
+            "--canonical" => {
+                choice = PlanChoice::Canonical;
+                continue;
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            positions => SwitchSetting::Positions(positions.to_string()),
+        };
+        if from.replace(setting).is_some() {
+            return Err("plan takes a single starting setting".to_string());
+        }
+    }
+
+    let from = from.ok_or("plan requires the current switch positions or --raw <VALUE>")?;
+    let target = match (to, group1) {
+        (Some(_), Some(_)) => {
+            return Err("plan takes either --to or --group1, not both".to_string());
+        }
+        (Some(to), None) => {
+            if group2.is_some() || sd_video.is_some() {
+                return Err("--group2, --bars and --black only apply with --group1".to_string());
+            }
+            PlanTarget::Setting(to)
+        }
+        (None, Some(group1)) => PlanTarget::Config(ConfigTarget {
+            group1,
+            group2: group2.unwrap_or(group1),
+            sd_video,
+        }),
+        (None, None) => {
+            return Err("plan requires --to <POSITIONS> or --group1 <FORMAT>".to_string());
+        }
+    };
+    Ok(Command::Plan {
+        from,
+        target,
+        choice,
+    })
+}
+
+fn parse_preset(args: &[String]) -> Result<Command, String> {
+    let (action, rest) = args
+        .split_first()
+        .ok_or("preset requires list, add or remove")?;
+    let command = match (action.as_str(), rest) {
+        ("list", []) => PresetCommand::List,
+        ("add", [name, positions]) if !positions.starts_with("--") => PresetCommand::Add {
+            name: name.clone(),
+            setting: SwitchSetting::Positions(positions.clone()),
+        },
+        ("add", [name, raw, value]) if raw == "--raw" => PresetCommand::Add {
+            name: name.clone(),
+            setting: SwitchSetting::Raw(value.clone()),
+        },
+        ("add", _) => {
+            return Err(
+                "preset add requires a name and switch positions or --raw <VALUE>".to_string(),
+            );
+        }
+        ("remove", [name]) => PresetCommand::Remove { name: name.clone() },
+        ("remove", _) => return Err("preset remove requires a single name".to_string()),
+        ("list", _) => return Err("preset list takes no arguments".to_string()),
+        (other, _) => return Err(format!("unknown preset command '{}'", other)),
+    };
+    Ok(Command::Preset(command))
+}
+
+/// Parse a raw value in hex (0x1B), binary (0b00011011) or decimal (27)
+fn parse_raw(profile: &Profile, value: &str) -> Result<DipSwitch, String> {
+    let lower = value.to_ascii_lowercase();
+    if lower.starts_with("0x") || lower.starts_with("0b") {
+        return profile.parse_switches(value).map_err(|e| e.to_string());
+    }
+    match value.parse::<u8>() {
+        Ok(raw) => profile
+            .parse_switches(&format!("{:#x}", raw))
+            .map_err(|e| e.to_string()),
+        Err(_) => Err(format!("invalid raw switch value '{}'", value)),
+    }
+}
+
+/// Run a non-interactive command, printing its result to stdout
+///
+/// `presets` is the preset file used by the preset commands.
+pub fn run(command: &Command, profile: &Profile, presets: Option<&Path>) -> Result<(), String> {
+    match command {
+        Command::Plan {
+            from,
+            target,
+            choice,
+        } => {
+            let from = from.resolve(profile)?;
+            let plan = match target {
+                PlanTarget::Setting(to) => {
+                    TogglePlan::between(profile, &from, &to.resolve(profile)?)
+                }
+                PlanTarget::Config(target) => {
+                    let target = target.resolve(profile);
+                    TogglePlan::to_config(profile, &from, &target, *choice).ok_or_else(|| {
+                        format!(
+                            "no switch setting produces Group 1 {} with Group 2 {}",
+                            target.group1, target.group2
+                        )
+                    })?
+                }
+            };
+            print!("{}", plan_text(profile, &plan, *choice));
+        }
+        Command::Preset(preset) => {
+            let path =
+                presets.ok_or("no config directory for presets (set HOME or use --presets)")?;
+            print!("{}", run_preset(preset, profile, path)?);
+        }
+        Command::Tui => {}
+        Command::Help => println!("{}", USAGE),
+        Command::Decode { setting, output } => {
+            let dipswitch = setting.resolve(profile)?;
+            match output {
+                OutputFormat::Text => print!("{}", decode_text(profile, &dipswitch)),
+                OutputFormat::Json => println!("{}", decode_json(profile, &dipswitch)),
+            }
+        }
+        Command::Encode { target } => print!("{}", encode_text(profile, target)?),
+        Command::Audio {
+            setting,
+            out,
+            seconds,
+            group,
+        } => {
+            let dipswitch = match setting {
+                Some(setting) => setting.resolve(profile)?,
+                None => DipSwitch::new(),
+            };
+            let config = profile.config(&dipswitch);
+            let format = [config.group1_format, config.group2_format][*group];
+            let audio = config.audio_reference();
+            if audio.clip_len(format.frame_rate, *seconds) > AudioClip::MAX_WAV_SAMPLES {
+                return Err(format!(
+                    "--seconds {} is too long for a WAV file (at most {} s)",
+                    seconds,
+                    AudioClip::MAX_WAV_SAMPLES / audio.sample_rate as u64
+                ));
+            }
+            let clip = audio.clip(format.frame_rate, *seconds);
+            let wav = clip.to_wav().ok_or("the clip is too long for a WAV file")?;
+            fs::write(out, wav).map_err(|e| format!("cannot write '{}': {}", out.display(), e))?;
+            print!("{}", audio_text(&config, &format, &clip, out));
+        }
+        Command::Waveform {
+            setting,
+            out,
+            group,
+            span,
+            format,
+            oversample,
+        } => {
+            let config = profile.config(&setting.resolve(profile)?);
+            let video = [config.group1_format, config.group2_format][*group];
+            let oversample = oversample.unwrap_or_else(|| Waveform::default_oversample(&video));
+            let waveform = video.reference_waveform(*span, oversample);
+            fs::write(out, waveform.encode(*format))
+                .map_err(|e| format!("cannot write '{}': {}", out.display(), e))?;
+            println!(
+                "Wrote {} {} reference, {} ({} samples at {:.3} MHz, {:.3} us, {}) to {}",
+                video.name(),
+                video.timing().sync.as_str(),
+                match span {
+                    WaveformSpan::Line => "one line",
+                    WaveformSpan::Field => "one field",
+                },
+                waveform.samples.len(),
+                waveform.sample_rate / 1e6,
+                waveform.duration() * 1e6,
+                format,
+                out.display()
+            );
+        }
+        Command::Frame {
+            setting,
+            out,
+            format,
+        } => {
+            let config = profile.config(&setting.resolve(profile)?);
+            let frame = SdFrame::new(config.sd_format, config.sd_video);
+            fs::write(out, frame.encode(*format))
+                .map_err(|e| format!("cannot write '{}': {}", out.display(), e))?;
+            println!(
+                "Wrote {} {} ({}x{}, {}) to {}",
+                config.sd_format.name(),
+                config.sd_video.as_str(),
+                frame.width(),
+                frame.height(),
+                format,
+                out.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The plan as a checklist under the two settings and their group formats
+fn plan_text(profile: &Profile, plan: &TogglePlan, choice: PlanChoice) -> String {
+    let mut out = String::new();
+    for (label, dipswitch) in [("From:", &plan.from), ("To:", &plan.to)] {
+        let config = profile.config(dipswitch);
+        let _ = writeln!(
+            out,
+            "{:<6}{}  Group 1 {}, Group 2 {}",
+            label,
+            profile.positions(dipswitch),
+            config.group1_format,
+            config.group2_format
+        );
+    }
+    if plan.equivalents > 1 {
+        let _ = writeln!(
+            out,
+            "      the {} of {} equivalent settings",
+            match choice {
+                PlanChoice::Canonical => "recommended one",
+                PlanChoice::FewestMoves => "one needing the fewest moves",
+            },
+            plan.equivalents
+        );
+    }
+    let _ = writeln!(out);
+    if plan.is_empty() {
+        let _ = writeln!(out, "Nothing to move: the switches are already set.");
+        return out;
+    }
+    for (step, switch_move) in plan.moves.iter().enumerate() {
+        let _ = writeln!(out, "[ ] {}. {}", step + 1, switch_move.describe(profile));
+    }
+    out
+}
+
+/// Run a preset command against the preset file at `path`
+fn run_preset(command: &PresetCommand, profile: &Profile, path: &Path) -> Result<String, String> {
+    let mut store = PresetStore::load(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let save = |store: &PresetStore| {
+        store
+            .save(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    };
+    let mut out = String::new();
+    match command {
+        PresetCommand::List => out.push_str(&preset_list_text(profile, &store, path)),
+        PresetCommand::Add { name, setting } => {
+            let dipswitch = setting.resolve(profile)?;
+            let replaced = store
+                .insert(name, dipswitch.clone())
+                .map_err(|e| e.to_string())?;
+            save(&store)?;
+            let _ = writeln!(
+                out,
+                "{} preset '{}' ({}) in {}",
+                if replaced { "Replaced" } else { "Saved" },
+                name.trim(),
+                profile.positions(&dipswitch),
+                path.display()
+            );
+        }
+        PresetCommand::Remove { name } => {
+            let removed = store
+                .remove(name)
+                .ok_or_else(|| format!("no preset named '{}' in {}", name, path.display()))?;
+            save(&store)?;
+            let _ = writeln!(
+                out,
+                "Removed preset '{}' from {}",
+                removed.name,
+                path.display()
+            );
+        }
+    }
+    Ok(out)
+}
+
+/// Presets with their positions and resulting group formats, one per line
+fn preset_list_text(profile: &Profile, store: &PresetStore, path: &Path) -> String {
+    let mut out = String::new();
+    if store.is_empty() {
+        let _ = writeln!(out, "No presets in {}", path.display());
+        return out;
+    }
+    let width = store
+        .presets()
+        .iter()
+        .map(|preset| preset.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for preset in store.presets() {
+        let config = profile.config(&preset.dipswitch);
+        let _ = writeln!(
+            out,
+            "{:<width$}  {}  Group 1 {}, Group 2 {}",
+            preset.name,
+            profile.positions(&preset.dipswitch),
+            config.group1_format,
+            config.group2_format,
+            width = width
+        );
+    }
+    out
+}
+
+/// Print a usage error and the usage text to stderr
+pub fn print_error(message: &str) {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+}
+
+/// Switch numbers as a legend under position strings (e.g. "12345678")
+fn switch_numbers(profile: &Profile) -> String {
+    (1..=profile.switch_count())
+        .map(|n| n.to_string())
+        .collect()
+}
+
+fn decode_text(profile: &Profile, dipswitch: &DipSwitch) -> String {
+    let config = profile.config(dipswitch);
+    let bitmask = dipswitch.to_raw() & profile.format_mask();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Switches:             {}  ({:#04X})",
+        profile.positions(dipswitch),
+        dipswitch
+    );
+    let _ = writeln!(out, "                      {}", switch_numbers(profile));
+    for (group, format) in [config.group1_format, config.group2_format]
+        .iter()
+        .enumerate()
+    {
+        let label = format!("{}:", profile.group_label(group));
+        let _ = writeln!(out, "{:<21} {}", label, format);
+    }
+    let _ = writeln!(out, "SD Output:            {}", config.sd_video.as_str());
+    let _ = writeln!(out, "Audio (AES-11):       {}", config.audio.as_str());
+    let _ = writeln!(
+        out,
+        "Video Format Bitmask: {:#010b}  {:#04X}",
+        bitmask, bitmask
+    );
+
+    let cadence = config.cadence();
+    let _ = writeln!(out, "Frames Align:         {}", cadence.alignment_summary());
+    if let Some(pulldown) = &cadence.pulldown {
+        let _ = writeln!(
+            out,
+            "Pulldown:             {} (group {})",
+            pulldown,
+            pulldown.source_group + 1
+        );
+    }
+    for warning in &cadence.warnings {
+        let _ = writeln!(out, "Warning:              {}", warning);
+    }
+    out
+}
+
+fn encode_text(profile: &Profile, target: &ConfigTarget) -> Result<String, String> {
+    let target = target.resolve(profile);
+    let solutions = solver::solve_with(profile, &target);
+    let Some((best, alternatives)) = solutions.split_first() else {
+        return Err(format!(
+            "no switch setting produces Group 1 {} with Group 2 {}",
+            target.group1, target.group2
+        ));
+    };
+
+    let config = profile.config(&best.dipswitch);
+    let mut out = String::new();
+    for (group, format) in [config.group1_format, config.group2_format]
+        .iter()
+        .enumerate()
+    {
+        let label = format!("{}:", profile.group_label(group));
+        let _ = writeln!(out, "{:<21} {}", label, format);
+    }
+    let _ = writeln!(out, "SD Output:            {}", config.sd_video.as_str());
+    let _ = writeln!(out, "Audio (AES-11):       {}", config.audio.as_str());
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "Switches:             {}  (raw {:#04X}, {:#010b})",
+        profile.positions(&best.dipswitch),
+        best.dipswitch,
+        best.dipswitch
+    );
+    let _ = writeln!(out);
+    out.push_str(&switch_diagram(profile, &best.dipswitch));
+
+    if !alternatives.is_empty() {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Equivalent settings:");
+        for alternative in alternatives {
+            let _ = writeln!(
+                out,
+                "  {}  ({:#04X})",
+                profile.positions(&alternative.dipswitch),
+                alternative.dipswitch
+            );
+        }
+    }
+    Ok(out)
+}
+
+/// ASCII drawing of the switch bank, one row per switch like the TUI
+fn switch_diagram(profile: &Profile, dipswitch: &DipSwitch) -> String {
+    let mut out = String::new();
+    for (index, spec) in profile.switches.iter().enumerate() {
+        let lever = if dipswitch.get(index) {
+            "[----o]"
+        } else {
+            "[o----]"
+        };
+        let _ = writeln!(
+            out,
+            "  {:>6}  {}  {}  {:<5}  {}",
+            spec.left.as_deref().unwrap_or(""),
+            index + 1,
+            lever,
+            spec.right.as_deref().unwrap_or(""),
+            spec.function
+        );
+    }
+    out
+}
+
+fn audio_text(config: &OutputConfig, format: &VideoFormat, clip: &AudioClip, out: &Path) -> String {
+    let audio = config.audio_reference();
+    let content = match audio.tone {
+        Some(tone) => format!("{} Hz tone at {} dBFS", tone.frequency_hz, tone.level_dbfs),
+        None => "silence".to_string(),
+    };
+    let mut text = String::new();
+    let _ = writeln!(text, "Wrote {} to {}", content, out.display());
+    let _ = writeln!(
+        text,
+        "{} Hz, 24-bit, {} channels: {} samples ({:.3} s)",
+        clip.sample_rate,
+        AudioClip::CHANNELS,
+        clip.samples.len(),
+        clip.duration()
+    );
+    let _ = writeln!(
+        text,
+        "Aligned to {} frames of {} ({} samples per frame)",
+        clip.frames,
+        format.name(),
+        clip.samples_per_frame
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join("/")
+    );
+    text
+}
+
+fn decode_json(profile: &Profile, dipswitch: &DipSwitch) -> String {
+    let config = profile.config(dipswitch);
+    let decoded = DecodeJson {
+        switches: profile.positions(dipswitch),
+        raw: dipswitch.to_raw(),
+        group1: FormatJson::new(&config.group1_format),
+        group2: FormatJson::new(&config.group2_format),
+        sd_video: config.sd_video.as_str(),
+        audio: config.audio.as_str(),
+        bitmask: dipswitch.to_raw() & profile.format_mask(),
+        cadence: CadenceJson::new(&config.cadence()),
+    };
+    serde_json::to_string(&decoded).expect("decode output serializes")
+}
+
+/// `decode --json` output
+#[derive(Serialize)]
+struct DecodeJson<'a> {
+    switches: String,
+    raw: u8,
+    group1: FormatJson<'a>,
+    group2: FormatJson<'a>,
+    sd_video: &'a str,
+    audio: &'a str,
+    bitmask: u8,
+    cadence: CadenceJson,
+}
+
+#[derive(Serialize)]
+struct FormatJson<'a> {
+    name: String,
+    standard: &'a str,
+    active_lines: u16,
+    scan: &'a str,
+    frame_rate: String,
+    frame_rate_num: u32,
+    frame_rate_den: u32,
+}
+
+impl<'a> FormatJson<'a> {
+    fn new(format: &'a VideoFormat) -> Self {
+        Self {
+            name: format.to_string(),
+            standard: format.standard.as_str(),
+            active_lines: format.active_lines,
+            scan: format.scan.as_str(),
+            frame_rate: format.frame_rate.to_string(),
+            frame_rate_num: format.frame_rate.numerator(),
+            frame_rate_den: format.frame_rate.denominator(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CadenceJson {
+    frames_aligned: bool,
+    alignment_period: String,
+    alignment_frames: [u64; 2],
+    pulldown: Option<PulldownJson>,
+    warnings: Vec<String>,
+}
+
+impl CadenceJson {
+    fn new(cadence: &CadenceAnalysis) -> Self {
+        Self {
+            frames_aligned: cadence.frames_aligned,
+            alignment_period: cadence.alignment_period.to_string(),
+            alignment_frames: cadence.alignment_frames,
+            pulldown: cadence.pulldown.as_ref().map(|pulldown| PulldownJson {
+                source_group: pulldown.source_group + 1,
+                pattern: pulldown.pattern.clone(),
+                fields: pulldown.fields,
+                name: pulldown.to_string(),
+            }),
+            warnings: cadence.warnings.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PulldownJson {
+    /// 1-based group whose frames are carried
+    source_group: usize,
+    pattern: Vec<u64>,
+    fields: bool,
+    name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn command(list: &[&str]) -> Command {
+        parse_args(&args(list)).unwrap().command
+    }
+
+    fn command_preset(list: &[&str]) -> PresetCommand {
+        let Command::Preset(preset) = command(list) else {
+            panic!("expected preset command");
+        };
+        preset
+    }
+
+    fn encode_target(list: &[&str]) -> ConfigTarget {
+        let Command::Encode { target } = command(list) else {
+            panic!("expected encode command");
+        };
+        target
+    }
+
+    #[test]
+    fn test_no_args_starts_tui() {
+        let cli = parse_args(&[]).unwrap();
+        assert!(matches!(cli.command, Command::Tui));
+        assert_eq!(cli.profile, None);
+    }
+
+    #[test]
+    fn test_profile_option() {
+        let cli = parse_args(&args(&["--profile", "unit.toml", "decode", "LLLLLLLL"])).unwrap();
+        assert_eq!(cli.profile, Some(PathBuf::from("unit.toml")));
+        assert!(matches!(cli.command, Command::Decode { .. }));
+
+        assert!(parse_args(&args(&["--profile"])).is_err());
+    }
+
+    #[test]
+    fn test_decode_positions() {
+        let Command::Decode { setting, output } = command(&["decode", "RRLLLLLL", "--json"]) else {
+            panic!("expected decode command");
+        };
+        assert_eq!(output, OutputFormat::Json);
+        let dipswitch = setting.resolve(Profile::gen10()).unwrap();
+        assert!(dipswitch.get(0));
+        assert!(dipswitch.get(1));
+        assert!(!dipswitch.get(2));
+    }
+
+    #[test]
+    fn test_decode_raw_matches_positions() {
+        let profile = Profile::gen10();
+        let from_positions: DipSwitch = "RRLRRLLL".parse().unwrap();
+        assert_eq!(parse_raw(profile, "0x1B"), Ok(from_positions.clone()));
+        assert_eq!(parse_raw(profile, "0b00011011"), Ok(from_positions.clone()));
+        assert_eq!(parse_raw(profile, "27"), Ok(from_positions));
+        assert!(parse_raw(profile, "RRLRRLLL").is_err());
+        assert!(parse_raw(profile, "0x100").is_err());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let profile = Profile::gen10();
+        assert!(parse_args(&args(&["decode"])).is_err());
+        assert!(parse_args(&args(&["decode", "LLLLLLLL", "--raw", "0"])).is_err());
+        assert!(parse_args(&args(&["frobnicate"])).is_err());
+        assert!(
+            SwitchSetting::Positions("LRL".into())
+                .resolve(profile)
+                .is_err()
+        );
+        assert!(
+            SwitchSetting::Positions("LRLXLRLL".into())
+                .resolve(profile)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_decode_cadence() {
+        let profile = Profile::gen10();
+        let text = decode_text(profile, &"RLLLLLRL".parse().unwrap());
+        assert!(text.contains("Pulldown:             2:3 pulldown (group 1)"));
+        let text = decode_text(profile, &"RLLLLLLR".parse().unwrap());
+        assert!(text.contains("Warning:              1000/1001 rate mismatch"));
+
+        let json = decode_json(profile, &"RLLLLLRL".parse().unwrap());
+        assert!(json.contains(
+            "\"alignment_frames\":[4,5],\"pulldown\":{\"source_group\":1,\"pattern\":[2,3]"
+        ));
+    }
+
+    #[test]
+    fn test_frame_args() {
+        let Command::Frame {
+            setting,
+            out,
+            format,
+        } = command(&["frame", "LLRRLLLL", "--out", "bars.v210"])
+        else {
+            panic!("expected frame command");
+        };
+        assert_eq!(setting, SwitchSetting::Positions("LLRRLLLL".into()));
+        assert_eq!(out, PathBuf::from("bars.v210"));
+        assert_eq!(format, FrameFileFormat::V210);
+
+        let Command::Frame { format, .. } = command(&[
+            "frame", "--raw", "0x04", "--out", "bars.bin", "--format", "uyvy",
+        ]) else {
+            panic!("expected frame command");
+        };
+        assert_eq!(format, FrameFileFormat::Uyvy10);
+
+        assert!(parse_args(&args(&["frame", "LLLLLLLL", "--out", "bars.bin"])).is_err());
+        assert!(parse_args(&args(&["frame", "LLLLLLLL"])).is_err());
+    }
+
+    #[test]
+    fn test_audio_args() {
+        let Command::Audio {
+            setting,
+            out,
+            seconds,
+            group,
+        } = command(&["audio", "--out", "tone.wav", "--seconds", "2.5"])
+        else {
+            panic!("expected audio command");
+        };
+        assert_eq!(setting, None);
+        assert_eq!(out, PathBuf::from("tone.wav"));
+        assert_eq!((seconds, group), (2.5, 0));
+
+        let Command::Audio { setting, group, .. } =
+            command(&["audio", "LLRLLLLL", "--group", "2", "--out", "tone.wav"])
+        else {
+            panic!("expected audio command");
+        };
+        assert_eq!(setting, Some(SwitchSetting::Positions("LLRLLLLL".into())));
+        assert_eq!(group, 1);
+
+        assert!(parse_args(&args(&["audio", "--out", "a.wav", "--seconds", "-1"])).is_err());
+        assert!(parse_args(&args(&["audio", "--out", "a.wav", "--group", "3"])).is_err());
+        assert!(parse_args(&args(&["audio"])).is_err());
+    }
+
+    #[test]
+    fn test_waveform_args() {
+        let Command::Waveform {
+            setting,
+            out,
+            group,
+            span,
+            format,
+            oversample,
+        } = command(&["waveform", "LLLLLLLR", "--out", "sync.csv"])
+        else {
+            panic!("expected waveform command");
+        };
+        assert_eq!(setting, SwitchSetting::Positions("LLLLLLLR".into()));
+        assert_eq!(out, PathBuf::from("sync.csv"));
+        assert_eq!(
+            (group, span, format, oversample),
+            (0, WaveformSpan::Line, WaveformFileFormat::Csv, None)
+        );
+
+        let Command::Waveform {
+            group,
+            span,
+            format,
+            oversample,
+            ..
+        } = command(&[
+            "waveform",
+            "--raw",
+            "0x80",
+            "--group",
+            "2",
+            "--span",
+            "field",
+            "--out",
+            "sync.raw",
+            "--oversample",
+            "8",
+        ])
+        else {
+            panic!("expected waveform command");
+        };
+        assert_eq!(
+            (group, span, format, oversample),
+            (1, WaveformSpan::Field, WaveformFileFormat::F32, Some(8))
+        );
+
+        assert!(parse_args(&args(&["waveform", "LLLLLLLL", "--out", "sync.bin"])).is_err());
+        assert!(
+            parse_args(&args(&[
+                "waveform", "LLLLLLLL", "--out", "a.csv", "--span", "frame"
+            ]))
+            .is_err()
+        );
+        assert!(
+            parse_args(&args(&[
+                "waveform",
+                "LLLLLLLL",
+                "--out",
+                "a.csv",
+                "--oversample",
+                "0"
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let profile = Profile::gen10();
+        let text = decode_text(profile, &"RLRLLLLL".parse().unwrap());
+        assert!(text.contains("Group 1 (Out 1-4):    HD - 1080i59.94 (1080psf29.97)"));
+        assert!(text.contains("Group 2 (Out 5-6):    SD - 525i (NTSC)"));
+        assert!(text.contains("SD Output:            75% Color Bars"));
+    }
+
+    #[test]
+    fn test_encode_args() {
+        let target = encode_target(&["encode", "--group1", "1080p25", "--group2", "sd", "--bars"]);
+        assert_eq!(target.group2, GroupTarget::Sd);
+        assert_eq!(target.sd_video, Some(SdVideo::ColorBars75));
+
+        assert!(parse_args(&args(&["encode"])).is_err());
+        assert!(parse_args(&args(&["encode", "--group1", "999p1"])).is_err());
+    }
+
+    #[test]
+    fn test_encode_text() {
+        let target = encode_target(&["encode", "--group1", "720p50"]);
+        let text = encode_text(Profile::gen10(), &target).unwrap();
+        assert!(text.contains("Switches:             RRLRRLLL  (raw 0x1B, 0b00011011)"));
+        assert!(text.contains("      SD  1  [----o]  HD     OUT 1-4"));
+        assert!(text.contains("Equivalent settings:"));
+    }
+
+    #[test]
+    fn test_encode_interlaced_frame_rate() {
+        let field_rate = encode_target(&["encode", "--group1", "1080i50"]);
+        let frame_rate = encode_target(&["encode", "--group1", "1080i25"]);
+        assert_eq!(
+            encode_text(Profile::gen10(), &frame_rate),
+            encode_text(Profile::gen10(), &field_rate)
+        );
+    }
+
+    #[test]
+    fn test_encode_impossible() {
+        let target = encode_target(&["encode", "--group1", "720p50", "--group2", "720p59.94"]);
+        assert!(encode_text(Profile::gen10(), &target).is_err());
+    }
+
+    #[test]
+    fn test_preset_args() {
+        assert_eq!(
+            command_preset(&["preset", "add", "OB truck", "RRLRRLLL"]),
+            PresetCommand::Add {
+                name: "OB truck".to_string(),
+                setting: SwitchSetting::Positions("RRLRRLLL".to_string()),
+            }
+        );
+        assert_eq!(
+            command_preset(&["preset", "add", "Studio", "--raw", "0x03"]),
+            PresetCommand::Add {
+                name: "Studio".to_string(),
+                setting: SwitchSetting::Raw("0x03".to_string()),
+            }
+        );
+        assert_eq!(command_preset(&["preset", "list"]), PresetCommand::List);
+        assert_eq!(
+            command_preset(&["preset", "remove", "Studio"]),
+            PresetCommand::Remove {
+                name: "Studio".to_string()
+            }
+        );
+        for bad in [
+            &["preset"][..],
+            &["preset", "add", "Studio"],
+            &["preset", "remove"],
+            &["preset", "rename", "a", "b"],
+        ] {
+            assert!(parse_args(&args(bad)).is_err(), "{:?}", bad);
+        }
+
+        let cli = parse_args(&args(&["--presets", "p.toml", "--preset", "Studio"])).unwrap();
+        assert_eq!(cli.presets, Some(PathBuf::from("p.toml")));
+        assert_eq!(cli.preset.as_deref(), Some("Studio"));
+        assert!(matches!(cli.command, Command::Tui));
+        // A preset only picks the simulator's starting setting
+        assert!(parse_args(&args(&["--preset", "Studio", "decode", "LLLLLLLL"])).is_err());
+
+        let cli = parse_args(&args(&["--theme", "colorblind"])).unwrap();
+        assert_eq!(cli.theme.as_deref(), Some("colorblind"));
+        assert!(parse_args(&args(&["--theme", "colorblind", "decode", "LLLLLLLL"])).is_err());
+        assert!(parse_args(&args(&["--theme"])).is_err());
+    }
+
+    #[test]
+    fn test_preset_store_commands() {
+        let path =
+            std::env::temp_dir().join(format!("gen10ds-presets-{}.toml", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let profile = Profile::gen10();
+        let run = |list: &[&str]| run_preset(&command_preset(list), profile, &path);
+
+        assert!(run(&["preset", "list"]).unwrap().starts_with("No presets"));
+        assert!(
+            run(&["preset", "add", "Studio", "RRLLLLLL"])
+                .unwrap()
+                .starts_with("Saved")
+        );
+        assert!(
+            run(&["preset", "add", "studio", "RLLLLLLL"])
+                .unwrap()
+                .starts_with("Replaced")
+        );
+        let list = run(&["preset", "list"]).unwrap();
+        assert!(
+            list.starts_with("studio  RLLLLLLL  Group 1 HD - 1080i59.94"),
+            "{}",
+            list
+        );
+        assert!(run(&["preset", "remove", "STUDIO"]).is_ok());
+        assert!(run(&["preset", "remove", "Studio"]).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_plan_args() {
+        let Command::Plan {
+            from,
+            target,
+            choice,
+        } = command(&["plan", "RRLLLLLL", "--to", "RRLRRLLL"])
+        else {
+            panic!("expected plan command");
+        };
+        assert_eq!(from, SwitchSetting::Positions("RRLLLLLL".to_string()));
+        assert!(matches!(
+            target,
+            PlanTarget::Setting(SwitchSetting::Positions(_))
+        ));
+        assert_eq!(choice, PlanChoice::FewestMoves);
+
+        let Command::Plan {
+            from,
+            target,
+            choice,
+        } = command(&[
+            "plan",
+            "--raw",
+            "0x03",
+            "--group1",
+            "720p50",
+            "--bars",
+            "--canonical",
+        ])
+        else {
+            panic!("expected plan command");
+        };
+        assert_eq!(from, SwitchSetting::Raw("0x03".to_string()));
+        let PlanTarget::Config(target) = target else {
+            panic!("expected a config target");
+        };
+        assert_eq!(target.group2, target.group1);
+        assert_eq!(target.sd_video, Some(SdVideo::ColorBars75));
+        assert_eq!(choice, PlanChoice::Canonical);
+
+        for bad in [
+            &["plan", "RRLLLLLL"][..],
+            &["plan", "--to", "RRLLLLLL"],
+            &["plan", "RRLLLLLL", "--to", "LLLLLLLL", "--group1", "720p50"],
+            &["plan", "RRLLLLLL", "--to", "LLLLLLLL", "--bars"],
+            &[
+                "plan", "RRLLLLLL", "--group1", "720p50", "--bars", "--black",
+            ],
+        ] {
+            assert!(parse_args(&args(bad)).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_plan_text() {
+        let profile = Profile::gen10();
+        let from: DipSwitch = "RRLLLLLL".parse().unwrap();
+        let plan = TogglePlan::between(profile, &from, &"RRLRRLLL".parse().unwrap());
+        let text = plan_text(profile, &plan, PlanChoice::FewestMoves);
+        assert!(text.contains("To:   RRLRRLLL  Group 1 HD - 720p50, Group 2 HD - 720p50\n"));
+        assert!(text.ends_with("[ ] 1. Move SW4 to RIGHT (50)\n[ ] 2. Move SW5 to RIGHT (720)\n"));
+
+        let plan = TogglePlan::between(profile, &from, &from);
+        assert!(plan_text(profile, &plan, PlanChoice::FewestMoves).contains("Nothing to move"));
+    }
+
+    #[test]
+    fn test_decode_json_fields() {
+        let profile = Profile::gen10();
+        let json = decode_json(profile, &"RLLLLLRL".parse().unwrap());
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["switches"], "RLLLLLRL");
+        assert_eq!(value["raw"], 0x41);
+        assert_eq!(value["group1"]["name"], "HD - 1080p23.98");
+        assert_eq!(value["group1"]["frame_rate_num"], 24000);
+        assert_eq!(value["group2"]["scan"], "i");
+        assert_eq!(value["cadence"]["pulldown"]["name"], "2:3 pulldown");
+        assert!(value["cadence"]["warnings"].is_array());
+    }
+}
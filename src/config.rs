@@ -0,0 +1,95 @@
+//! Files kept in the user's XDG config directory (`$XDG_CONFIG_HOME`,
+//! falling back to `~/.config`), such as presets and themes.
+
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Error reading, writing or validating a config file
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(String),
+    /// The file parsed but its content is unusable; the message names the
+    /// offending entry
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "cannot access file: {}", err),
+            ConfigError::Parse(message) => write!(f, "cannot parse file: {}", message),
+            ConfigError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::Parse(_) | ConfigError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+/// Location of a file in the user's config directory, if one is known
+pub(crate) fn user_config_file(name: &str) -> Option<PathBuf> {
+    config_dir(
+        std::env::var_os("XDG_CONFIG_HOME"),
+        std::env::var_os("HOME"),
+    )
+    .map(|dir| dir.join(name))
+}
+
+/// Read a config file; a missing file is `None`
+pub(crate) fn read_optional(path: impl AsRef<Path>) -> Result<Option<String>, ConfigError> {
+    match fs::read_to_string(path) {
+        Ok(source) => Ok(Some(source)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// XDG config directory: `$XDG_CONFIG_HOME` if it is absolute, else `~/.config`
+fn config_dir(xdg_config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    xdg_config_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| PathBuf::from(home).join(".config")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_dir() {
+        assert_eq!(
+            config_dir(Some("/xdg".into()), Some("/home/me".into())),
+            Some(PathBuf::from("/xdg"))
+        );
+        // A relative XDG_CONFIG_HOME is ignored
+        assert_eq!(
+            config_dir(Some("rel".into()), Some("/home/me".into())),
+            Some(PathBuf::from("/home/me/.config"))
+        );
+        assert_eq!(config_dir(None, None), None);
+    }
+
+    #[test]
+    fn test_missing_file_reads_as_none() {
+        let path = std::env::temp_dir().join("gen10ds-no-such-dir/none.toml");
+        assert!(read_optional(&path).unwrap().is_none());
+    }
+}
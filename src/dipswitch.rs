@@ -1,8 +1,13 @@
-use crate::format_calculator;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::profile::Profile;
+use crate::video_format::VideoFormat;
 
 /// Represents the 8 DIP switches
 /// false = LEFT, true = RIGHT
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DipSwitch {
     switches: [bool; 8],
 }
@@ -15,6 +20,23 @@ impl DipSwitch {
         }
     }
 
+    /// Create from a full 8-bit value where bit 0 = switch 1, bit 7 = switch 8
+    pub fn from_raw(raw: u8) -> Self {
+        let mut switches = [false; 8];
+        for (i, switch) in switches.iter_mut().enumerate() {
+            *switch = raw & (1 << i) != 0;
+        }
+        Self { switches }
+    }
+
+    /// Full 8-bit value of all switches (unlike `get_bitmask`, nothing is masked)
+    pub fn to_raw(&self) -> u8 {
+        self.switches
+            .iter()
+            .enumerate()
+            .fold(0, |raw, (i, &switch)| raw | ((switch as u8) << i))
+    }
+
     /// Toggle switch at index (0-7, maps to switches 1-8)
     pub fn toggle(&mut self, index: usize) {
         if index < 8 {
@@ -22,6 +44,13 @@ impl DipSwitch {
         }
     }
 
+    /// Set switch at index (0-7) to a position (false = LEFT, true = RIGHT)
+    pub fn set(&mut self, index: usize, state: bool) {
+        if index < 8 {
+            self.switches[index] = state;
+        }
+    }
+
     /// Reset all switches to default (LEFT) position
     pub fn reset(&mut self) {
         self.switches = [false; 8];
@@ -39,13 +68,7 @@ impl DipSwitch {
     /// Calculate 8-bit bitmask where bit 0 = switch 1, bit 7 = switch 8
     /// Masked with 0b1111_1000 to show only video format related switches (4-8)
     pub fn get_bitmask(&self) -> u8 {
-        let mut mask = 0u8;
-        for (i, &switch) in self.switches.iter().enumerate() {
-            if switch {
-                mask |= 1 << i;
-            }
-        }
-        mask & 0b1111_1000
+        self.to_raw() & 0b1111_1000
     }
 
     /// Get bitmask as binary string (e.g., "0b00001101")
@@ -58,51 +81,9 @@ impl DipSwitch {
         format!("0x{:02X}", self.get_bitmask())
     }
 
-    /// Calculate current output configuration
+    /// Calculate current output configuration (built-in GEN10 profile)
     pub fn get_config(&self) -> OutputConfig {
-        // Switch indices (0-based)
-        let sw1_sync_1_4 = self.switches[0];
-        let sw2_sync_5_6 = self.switches[1];
-        let sw3_sd_audio = self.switches[2];
-        let sw4_format = self.switches[3];
-        let sw5_hd_fmt = self.switches[4];
-        let sw6_s1 = self.switches[5];
-        let sw7_s2 = self.switches[6];
-        let sw8_s3 = self.switches[7];
-
-        // Calculate Group 1 format (outputs 1-4)
-        let group1_format = format_calculator::calculate_group_format(
-            sw1_sync_1_4,
-            sw4_format,
-            sw5_hd_fmt,
-            sw6_s1,
-            sw7_s2,
-            sw8_s3,
-        );
-
-        // Calculate Group 2 format (outputs 5-6)
-        let group2_format = format_calculator::calculate_group_format(
-            sw2_sync_5_6,
-            sw4_format,
-            sw5_hd_fmt,
-            sw6_s1,
-            sw7_s2,
-            sw8_s3,
-        );
-
-        // SD Output and Audio (both controlled by switch 3)
-        let (sd_video, audio) = if sw3_sd_audio {
-            (SdVideo::ColorBars75, AudioOutput::Tone)
-        } else {
-            (SdVideo::ColorBlack, AudioOutput::Silent)
-        };
-
-        OutputConfig {
-            group1_format,
-            group2_format,
-            sd_video,
-            audio,
-        }
+        Profile::gen10().config(self)
     }
 }
 
@@ -112,11 +93,102 @@ impl Default for DipSwitch {
     }
 }
 
+impl fmt::Display for DipSwitch {
+    /// Position string with switch 1 first, L = LEFT and R = RIGHT (e.g. "LRRLLRLL")
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &switch in &self.switches {
+            f.write_str(if switch { "R" } else { "L" })?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::UpperHex for DipSwitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.to_raw(), f)
+    }
+}
+
+impl fmt::LowerHex for DipSwitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.to_raw(), f)
+    }
+}
+
+impl fmt::Binary for DipSwitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Binary::fmt(&self.to_raw(), f)
+    }
+}
+
+/// Error returned when a switch setting string cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDipSwitchError(String);
+
+impl ParseDipSwitchError {
+    pub(crate) fn new(message: String) -> Self {
+        Self(message)
+    }
+}
+
+impl fmt::Display for ParseDipSwitchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for ParseDipSwitchError {}
+
+impl FromStr for DipSwitch {
+    type Err = ParseDipSwitchError;
+
+    /// Parse a position string ("LRRLLRLL", switch 1 first) or a full raw
+    /// value in hex ("0x1B") or binary ("0b00011011")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let lower = s.to_ascii_lowercase();
+        let radix = if let Some(hex) = lower.strip_prefix("0x") {
+            Some((hex, 16))
+        } else {
+            lower.strip_prefix("0b").map(|bin| (bin, 2))
+        };
+        if let Some((digits, radix)) = radix {
+            return u8::from_str_radix(digits, radix)
+                .map(Self::from_raw)
+                .map_err(|_| ParseDipSwitchError(format!("invalid raw switch value '{}'", s)));
+        }
+
+        if s.chars().count() != 8 {
+            return Err(ParseDipSwitchError(format!(
+                "expected 8 switch positions (L/R), got '{}'",
+                s
+            )));
+        }
+        let mut switches = [false; 8];
+        for (switch, c) in switches.iter_mut().zip(s.chars()) {
+            *switch = match c.to_ascii_uppercase() {
+                'L' => false,
+                'R' => true,
+                _ => {
+                    return Err(ParseDipSwitchError(format!(
+                        "invalid switch position '{}' (expected L or R)",
+                        c
+                    )));
+                }
+            };
+        }
+        Ok(Self { switches })
+    }
+}
+
 /// Complete output configuration based on DIP switch settings
 #[derive(Debug, Clone)]
 pub struct OutputConfig {
-    pub group1_format: String,
-    pub group2_format: String,
+    pub group1_format: VideoFormat,
+    pub group2_format: VideoFormat,
+    /// Format of the SD output, which is also the house reference the AES-11
+    /// output is locked to
+    pub sd_format: VideoFormat,
     pub sd_video: SdVideo,
     pub audio: AudioOutput,
 }
@@ -167,14 +239,25 @@ mod tests {
     fn test_toggle() {
         let mut ds = DipSwitch::new();
         ds.toggle(0); // Toggle switch 1
-        assert_eq!(ds.get(0), true);
+        assert!(ds.get(0));
         assert_eq!(ds.get_bitmask(), 0b00000000); // Masked out (not part of video format)
         
         ds.toggle(0); // Toggle back
-        assert_eq!(ds.get(0), false);
+        assert!(!ds.get(0));
         assert_eq!(ds.get_bitmask(), 0);
     }
 
+    #[test]
+    fn test_set() {
+        let mut ds = DipSwitch::new();
+        ds.set(4, true);
+        assert!(ds.get(4));
+        ds.set(4, true);
+        assert!(ds.get(4));
+        ds.set(4, false);
+        assert!(!ds.get(4));
+    }
+
     #[test]
     fn test_bitmask() {
         let mut ds = DipSwitch::new();
@@ -201,6 +284,57 @@ mod tests {
         assert_eq!(ds.get_bitmask_hex(), "0xF8");
     }
 
+    #[test]
+    fn test_raw_round_trip() {
+        for raw in 0..=u8::MAX {
+            assert_eq!(DipSwitch::from_raw(raw).to_raw(), raw);
+        }
+
+        let ds = DipSwitch::from_raw(0b0000_0101);
+        assert!(ds.get(0));
+        assert!(!ds.get(1));
+        assert!(ds.get(2));
+        assert_eq!(ds.get_bitmask(), 0);
+    }
+
+    #[test]
+    fn test_display_and_parse() {
+        let ds: DipSwitch = "RRLRRLLL".parse().unwrap();
+        assert_eq!(ds.to_raw(), 0x1B);
+        assert_eq!(ds.to_string(), "RRLRRLLL");
+        assert_eq!(format!("{:#04X}", ds), "0x1B");
+        assert_eq!(format!("{:08b}", ds), "00011011");
+
+        assert_eq!("0x1b".parse::<DipSwitch>(), Ok(ds.clone()));
+        assert_eq!("0b00011011".parse::<DipSwitch>(), Ok(ds.clone()));
+        assert_eq!("rrlrrlll".parse::<DipSwitch>(), Ok(ds));
+
+        for raw in 0..=u8::MAX {
+            let ds = DipSwitch::from_raw(raw);
+            assert_eq!(ds.to_string().parse::<DipSwitch>(), Ok(ds));
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("LRL".parse::<DipSwitch>().is_err());
+        assert!("LRLXLRLL".parse::<DipSwitch>().is_err());
+        assert!("0x100".parse::<DipSwitch>().is_err());
+        assert!("0b2".parse::<DipSwitch>().is_err());
+    }
+
+    #[test]
+    fn test_usable_as_map_key() {
+        use std::collections::HashMap;
+
+        let mut names = HashMap::new();
+        names.insert(DipSwitch::from_raw(0x1B), "720p50 house");
+        assert_eq!(
+            names.get(&"RRLRRLLL".parse().unwrap()),
+            Some(&"720p50 house")
+        );
+    }
+
     #[test]
     fn test_reset() {
         let mut ds = DipSwitch::new();
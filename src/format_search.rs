@@ -0,0 +1,244 @@
+//! Free-text format search over a profile's formats, accepting the common
+//! ways engineers write a format: "1080p25", "1080/50i", "1080i25" (frame
+//! rate), "29.97psf", "720 23.98".
+
+use crate::profile::Profile;
+use crate::video_format::{ScanType, Standard, VideoFormat};
+
+/// Rates closer than this (frames or fields per second) are the same rate
+const RATE_TOLERANCE: f64 = 0.01;
+
+/// A format matching a search query
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatMatch {
+    pub format: VideoFormat,
+    /// Table entries producing the format (empty for SD formats)
+    pub entries: Vec<usize>,
+    /// The rate only matches after rounding (e.g. "30" for 29.97)
+    pub approximate: bool,
+    /// Lower is a better match
+    rank: u8,
+}
+
+/// What a query asks for; `None` fields match anything
+#[derive(Debug, Clone, Default, PartialEq)]
+struct FormatQuery {
+    standard: Option<Standard>,
+    lines: Option<u16>,
+    scan: Option<ScanType>,
+    rate: Option<f64>,
+    /// Words that must appear in the format name
+    words: Vec<String>,
+}
+
+impl FormatQuery {
+    fn parse(query: &str) -> FormatQuery {
+        let mut parsed = FormatQuery::default();
+        for token in tokens(&query.to_ascii_lowercase()) {
+            if let Ok(number) = token.parse::<f64>() {
+                let lines = number as u16;
+                match lines {
+                    480 | 486 | 525 | 576 | 625 | 720 | 1080
+                        if parsed.lines.is_none() && number.fract() == 0.0 =>
+                    {
+                        parsed.lines = Some(lines);
+                    }
+                    _ => parsed.rate = Some(number),
+                }
+                continue;
+            }
+            match token.as_str() {
+                "i" => parsed.scan = Some(ScanType::Interlaced),
+                "p" => parsed.scan = Some(ScanType::Progressive),
+                "psf" | "sf" => parsed.scan = Some(ScanType::SegmentedFrame),
+                "sd" => parsed.standard = Some(Standard::Sd),
+                "hd" => parsed.standard = Some(Standard::Hd),
+                "ntsc" => parsed.lines = Some(525),
+                "pal" => parsed.lines = Some(625),
+                _ => parsed.words.push(token),
+            }
+        }
+        parsed
+    }
+
+    /// Rank of `format` for this query, `None` if it does not match
+    fn rank(&self, format: &VideoFormat) -> Option<(u8, bool)> {
+        if self
+            .standard
+            .is_some_and(|standard| standard != format.standard)
+        {
+            return None;
+        }
+        if let Some(lines) = self.lines {
+            let matches = match format.standard {
+                // 480 is the usual shorthand for 525-line SD
+                Standard::Sd => {
+                    lines == format.total_lines()
+                        || lines == format.active_lines
+                        || (lines == 480 && format.total_lines() == 525)
+                }
+                Standard::Hd => lines == format.active_lines,
+            };
+            if !matches {
+                return None;
+            }
+        }
+        if self.scan.is_some_and(|scan| scan != format.scan) {
+            return None;
+        }
+        let name = format.name().to_ascii_lowercase();
+        if !self.words.iter().all(|word| name.contains(word.as_str())) {
+            return None;
+        }
+
+        let Some(rate) = self.rate else {
+            return Some((0, false));
+        };
+        // Interlaced formats are named by field rate but often written by
+        // frame rate ("1080i25"); both count, the field rate first
+        let display = format.display_rate().as_f64();
+        let frame = format.frame_rate.as_f64();
+        if (rate - display).abs() < RATE_TOLERANCE {
+            Some((0, false))
+        } else if (rate - frame).abs() < RATE_TOLERANCE {
+            Some((1, false))
+        } else if rate.round() == display.round() {
+            Some((2, true))
+        } else if rate.round() == frame.round() {
+            Some((3, true))
+        } else {
+            None
+        }
+    }
+}
+
+/// Split a query into numbers and words: "1080/50i" gives "1080", "50", "i"
+fn tokens(query: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut previous: Option<bool> = None;
+    for c in query.chars() {
+        let numeric = c.is_ascii_digit() || c == '.';
+        if !numeric && !c.is_ascii_alphabetic() {
+            previous = None;
+            continue;
+        }
+        match tokens.last_mut() {
+            Some(token) if previous == Some(numeric) => token.push(c),
+            _ => tokens.push(c.to_string()),
+        }
+        previous = Some(numeric);
+    }
+    tokens
+}
+
+impl Profile {
+    /// Formats this unit can output that match a free-text query, best
+    /// matches first and in table order otherwise; an empty query lists all
+    ///
+    /// Cells carrying the same signal are merged into one match, and a PsF
+    /// alias from Table 2 ("1080psf29.97" for 1080i59.94) matches its cell.
+    pub fn search_formats(&self, query: &str) -> Vec<FormatMatch> {
+        let query = FormatQuery::parse(query);
+
+        let mut candidates: Vec<FormatMatch> = Vec::new();
+        for (entry, format) in self.table_entries() {
+            match candidates
+                .iter_mut()
+                .find(|c| c.format.same_signal(&format))
+            {
+                Some(candidate) => {
+                    candidate.entries.push(entry);
+                    candidate.format.psf_alias |= format.psf_alias;
+                }
+                None => candidates.push(FormatMatch {
+                    format,
+                    entries: vec![entry],
+                    approximate: false,
+                    rank: 0,
+                }),
+            }
+        }
+        candidates.extend(self.sd_formats.iter().map(|&format| FormatMatch {
+            format,
+            entries: Vec::new(),
+            approximate: false,
+            rank: 0,
+        }));
+
+        let mut matches: Vec<FormatMatch> = candidates
+            .into_iter()
+            .filter_map(|mut candidate| {
+                let names =
+                    std::iter::once(candidate.format).chain(candidate.format.psf_equivalent());
+                let (rank, approximate) = names.filter_map(|format| query.rank(&format)).min()?;
+                candidate.rank = rank;
+                candidate.approximate = approximate;
+                Some(candidate)
+            })
+            .collect();
+        matches.sort_by_key(|candidate| candidate.rank);
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(query: &str) -> Vec<String> {
+        Profile::gen10()
+            .search_formats(query)
+            .iter()
+            .map(|m| m.format.name())
+            .collect()
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(tokens("1080/50i"), ["1080", "50", "i"]);
+        assert_eq!(tokens("29.97psf"), ["29.97", "psf"]);
+        assert_eq!(tokens("720 23.98"), ["720", "23.98"]);
+    }
+
+    #[test]
+    fn test_exact_names() {
+        assert_eq!(names("1080p25"), ["1080p25"]);
+        // 24 only matches 23.98 after rounding, so it comes second
+        assert_eq!(names("720 23.98"), ["720p23.98", "720p24"]);
+        assert_eq!(names("625i"), ["625i (PAL)"]);
+    }
+
+    #[test]
+    fn test_common_notations() {
+        // EBU field-rate notation and frame-rate notation for interlaced
+        assert_eq!(names("1080/50i"), ["1080i50 (1080psf25)"]);
+        assert_eq!(names("1080i25"), ["1080i50 (1080psf25)"]);
+        // PsF alias listed in Table 2 finds its interlaced cell
+        assert_eq!(names("29.97psf")[0], "1080i59.94 (1080psf29.97)");
+        assert!(!names("29.97psf").contains(&"1080psf23.98".to_string()));
+        assert_eq!(names("ntsc"), ["525i (NTSC)"]);
+    }
+
+    #[test]
+    fn test_ranking() {
+        let matches = Profile::gen10().search_formats("1080p30");
+        assert_eq!(matches[0].format.name(), "1080p30");
+        assert!(!matches[0].approximate);
+        assert_eq!(matches[1].format.name(), "1080p29.97");
+        assert!(matches[1].approximate);
+
+        // Cells with the same signal are merged
+        let p25 = &Profile::gen10().search_formats("720p25")[0];
+        assert_eq!(p25.entries.len(), 6);
+    }
+
+    #[test]
+    fn test_no_match() {
+        assert!(names("1080p50").is_empty());
+        assert!(names("foo").is_empty());
+        assert_eq!(
+            names("").len(),
+            Profile::gen10().search_formats("hd").len() + 2
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+/// Default number of states kept for undo
+pub const HISTORY_LIMIT: usize = 100;
+
+/// Bounded undo/redo history of states, e.g. switch settings
+///
+/// The history always holds the current state; recording a new state drops
+/// any states that were undone, and the oldest state once the limit is hit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<T> {
+    states: VecDeque<T>,
+    position: usize,
+    limit: usize,
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    /// History holding only `initial`, keeping at most `limit` states
+    pub fn new(initial: T, limit: usize) -> Self {
+        Self {
+            states: VecDeque::from([initial]),
+            position: 0,
+            limit: limit.max(1),
+        }
+    }
+
+    /// The state at the current position
+    pub fn current(&self) -> &T {
+        &self.states[self.position]
+    }
+
+    /// Index of the current state in `states()`
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// All kept states, oldest first
+    pub fn states(&self) -> impl ExactSizeIterator<Item = &T> + '_ {
+        self.states.iter()
+    }
+
+    /// Record a new current state; a state equal to the current one is ignored
+    pub fn record(&mut self, state: T) {
+        if *self.current() == state {
+            return;
+        }
+        self.states.truncate(self.position + 1);
+        self.states.push_back(state);
+        if self.states.len() > self.limit {
+            self.states.pop_front();
+        }
+        self.position = self.states.len() - 1;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position + 1 < self.states.len()
+    }
+
+    /// Step back, returning the state to restore
+    pub fn undo(&mut self) -> Option<&T> {
+        self.jump(self.position.checked_sub(1)?)
+    }
+
+    /// Step forward again, returning the state to restore
+    pub fn redo(&mut self) -> Option<&T> {
+        self.jump(self.position + 1)
+    }
+
+    /// Move to any kept state, returning it
+    pub fn jump(&mut self, position: usize) -> Option<&T> {
+        if position >= self.states.len() {
+            return None;
+        }
+        self.position = position;
+        Some(self.current())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = History::new(0, 10);
+        assert!(!history.can_undo());
+        history.record(1);
+        history.record(2);
+        assert_eq!(history.undo(), Some(&1));
+        assert_eq!(history.undo(), Some(&0));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(&1));
+        assert_eq!(*history.current(), 1);
+
+        // Recording after an undo drops the undone states
+        history.record(5);
+        assert!(!history.can_redo());
+        assert_eq!(history.states().copied().collect::<Vec<_>>(), vec![0, 1, 5]);
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut history = History::new(0, 3);
+        for state in 1..=5 {
+            history.record(state);
+        }
+        assert_eq!(history.states().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(history.position(), 2);
+        assert_eq!(history.jump(0), Some(&3));
+        assert_eq!(history.jump(3), None);
+    }
+
+    #[test]
+    fn test_unchanged_state_not_recorded() {
+        let mut history = History::new(7, 3);
+        history.record(7);
+        assert_eq!(history.states().len(), 1);
+    }
+}
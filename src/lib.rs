@@ -0,0 +1,52 @@
+//! GEN10 DIP switch model and format calculator.
+//!
+//! The model ([`DipSwitch`], [`OutputConfig`]), the device [`Profile`] that
+//! drives the format lookup, the parser for the manual's switch tables
+//! (DIPSWITCHES.md), the Table 2 calculator, the reverse lookup solver, the
+//! free-text format search, the undo history, named presets, toggle plans,
+//! signal timing details, the group cadence analysis, the AES-11 audio
+//! details, the SD test frame export and the analog reference waveforms have
+//! no terminal dependencies. The interactive simulator (`app`, `ui` and
+//! `theme`) is only built with the `tui` feature, which is enabled by
+//! default; depend on this crate with `default-features = false` to use the
+//! library alone.
+
+pub mod audio;
+pub mod cadence;
+pub mod config;
+pub mod dipswitch;
+pub mod format_calculator;
+pub mod format_search;
+pub mod history;
+pub mod manual;
+pub mod plan;
+pub mod presets;
+pub mod profile;
+pub mod sd_frame;
+pub mod solver;
+pub mod timing;
+pub mod video_format;
+pub mod waveform;
+
+#[cfg(feature = "tui")]
+pub mod app;
+#[cfg(feature = "tui")]
+pub mod theme;
+#[cfg(feature = "tui")]
+pub mod ui;
+
+pub use audio::{AudioClip, AudioReference, GroupAudio, Tone};
+pub use cadence::{CadenceAnalysis, CadenceWarning, Pulldown};
+pub use config::ConfigError;
+pub use dipswitch::{AudioOutput, DipSwitch, OutputConfig, ParseDipSwitchError, SdVideo};
+pub use format_search::FormatMatch;
+pub use history::History;
+pub use manual::{Manual, ManualError};
+pub use plan::{PlanChoice, SwitchMove, TogglePlan};
+pub use presets::{Preset, PresetStore};
+pub use profile::{Profile, ProfileError, SwitchSpec};
+pub use sd_frame::{FrameFileFormat, SdFrame};
+pub use solver::{ConfigTarget, GroupTarget, Solution};
+pub use timing::{Rational, SignalTiming, SyncType, TimingStandard};
+pub use video_format::{FrameRate, ParseFormatError, ScanType, Standard, VideoFormat};
+pub use waveform::{Waveform, WaveformFileFormat, WaveformSpan};
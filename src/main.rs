@@ -1,19 +1,103 @@
-mod app;
-mod dipswitch;
-mod format_calculator;
-mod ui;
+mod cli;
 
 use std::io;
+use std::process;
+
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+
+use gen10ds::app;
+use gen10ds::theme::{Theme, ThemeConfig};
+use gen10ds::{PresetStore, Profile};
 
 fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cli = match cli::parse_args(&args) {
+        Ok(cli) => cli,
+        Err(message) => {
+            cli::print_error(&message);
+            process::exit(2);
+        }
+    };
+
+    let profile = match &cli.profile {
+        Some(path) => Profile::load(path).unwrap_or_else(|err| {
+            eprintln!("error: {}: {}", path.display(), err);
+            process::exit(1);
+        }),
+        None => Profile::gen10().clone(),
+    };
+
+    let presets_path = cli.presets.clone().or_else(PresetStore::default_path);
+
+    if !matches!(cli.command, cli::Command::Tui) {
+        if let Err(message) = cli::run(&cli.command, &profile, presets_path.as_deref()) {
+            eprintln!("error: {}", message);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // A broken preset file only stops the simulator if --preset needs it;
+    // otherwise it starts without presets and leaves the file untouched
+    let (presets, presets_path) = match presets_path {
+        Some(path) => match PresetStore::load(&path) {
+            Ok(presets) => (presets, Some(path)),
+            Err(err) if cli.preset.is_some() => {
+                eprintln!("error: {}: {}", path.display(), err);
+                process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("warning: {}: {}", path.display(), err);
+                (PresetStore::default(), None)
+            }
+        },
+        None => (PresetStore::default(), None),
+    };
+    let start = cli.preset.as_ref().map(|name| match presets.get(name) {
+        Some(preset) => preset.dipswitch.clone(),
+        None => {
+            eprintln!("error: no preset named '{}'", name);
+            process::exit(1);
+        }
+    });
+
+    let themes_path = ThemeConfig::default_path();
+    // As with presets, a broken themes file is only fatal with --theme
+    let themes = match &themes_path {
+        Some(path) => match ThemeConfig::load(path) {
+            Ok(themes) => themes,
+            Err(err) if cli.theme.is_some() => {
+                eprintln!("error: {}: {}", path.display(), err);
+                process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("warning: {}: {}", path.display(), err);
+                ThemeConfig::default()
+            }
+        },
+        None => ThemeConfig::default(),
+    };
+    let theme = Theme::select(cli.theme.as_deref(), &themes, Theme::no_color_requested())
+        .unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        });
+
     // Setup terminal
     let terminal = ratatui::init();
-    
+    crossterm::execute!(io::stdout(), EnableMouseCapture)?;
+
     // Run application
-    let mut app = app::App::new();
+    let mut app = app::App::with_profile(profile);
+    app.presets = app::PresetView::new(presets, presets_path);
+    app.theme = theme;
+    if let Some(dipswitch) = start {
+        app.start_with(dipswitch);
+    }
     let result = app.run(terminal);
     
     // Restore terminal
+    let _ = crossterm::execute!(io::stdout(), DisableMouseCapture);
     ratatui::restore();
     
     result
@@ -0,0 +1,611 @@
+//! Parser for the switch tables in the manual excerpt (DIPSWITCHES.md).
+//!
+//! Table 1 gives the meaning of each switch's LEFT and RIGHT position and
+//! Table 2 the HD format for every combination of the format switches. The
+//! parser checks the tables against each other and against the NOTE below
+//! Table 2, so an inconsistent document is rejected instead of silently
+//! producing a wrong lookup.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::video_format::{FrameRate, ScanType, VideoFormat};
+
+/// Error describing why the manual tables could not be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManualError(String);
+
+impl fmt::Display for ManualError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for ManualError {}
+
+/// Number of switches on the unit (see `DipSwitch`)
+const SWITCHES: usize = 8;
+
+fn error<T>(message: String) -> Result<T, ManualError> {
+    Err(ManualError(message))
+}
+
+/// One row of Table 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManualSwitch {
+    /// Switch number as printed on the unit (1-based)
+    pub number: usize,
+    pub function: String,
+    /// Description of the LEFT position
+    pub left: String,
+    /// Description of the RIGHT position
+    pub right: String,
+}
+
+/// One cell of Table 2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManualEntry {
+    /// Switch positions selecting this format as (switch number, RIGHT)
+    pub positions: Vec<(usize, bool)>,
+    pub format: VideoFormat,
+}
+
+/// Switch descriptions and format table parsed from the manual
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manual {
+    pub switches: Vec<ManualSwitch>,
+    /// Table 2 cells, column by column, top to bottom
+    pub formats: Vec<ManualEntry>,
+}
+
+impl Manual {
+    /// Parse Table 1 and Table 2 from the manual markdown
+    pub fn parse(markdown: &str) -> Result<Manual, ManualError> {
+        let table1 = section_table(markdown, "Table 1")?;
+        let switches = parse_switch_table(&table1)?;
+
+        let table2 = section_table(markdown, "Table 2")?;
+        let note = section_note(markdown, "Table 2");
+        let formats = parse_format_table(&table2, &switches, note.as_deref())?;
+
+        Ok(Manual { switches, formats })
+    }
+
+    /// Table 1 row for a switch number
+    pub fn switch(&self, number: usize) -> Option<&ManualSwitch> {
+        self.switches.iter().find(|switch| switch.number == number)
+    }
+}
+
+/// A markdown table row as trimmed cells, with bold markers removed
+type Row = Vec<String>;
+
+/// Lines of the `## <heading>` section
+fn section<'a>(markdown: &'a str, heading: &str) -> Option<Vec<&'a str>> {
+    let mut lines = markdown.lines();
+    lines.find(|line| {
+        line.strip_prefix("## ")
+            .is_some_and(|title| title.trim_start().starts_with(heading))
+    })?;
+    Some(lines.take_while(|line| !line.starts_with("## ")).collect())
+}
+
+/// Rows of the first table in a section, without the separator row
+fn section_table(markdown: &str, heading: &str) -> Result<Vec<Row>, ManualError> {
+    let lines = section(markdown, heading)
+        .ok_or_else(|| ManualError(format!("no '{}' section", heading)))?;
+    let rows: Vec<Row> = lines
+        .iter()
+        .map(|line| line.trim())
+        .skip_while(|line| !line.starts_with('|'))
+        .take_while(|line| line.starts_with('|'))
+        .map(split_row)
+        .filter(|row| {
+            !row.iter()
+                .all(|cell| cell.chars().all(|c| c == '-' || c == ':'))
+        })
+        .collect();
+    if rows.is_empty() {
+        return error(format!("'{}' section has no table", heading));
+    }
+    Ok(rows)
+}
+
+/// Text of the `**NOTE:**` paragraph in a section, if any
+fn section_note(markdown: &str, heading: &str) -> Option<String> {
+    section(markdown, heading)?
+        .iter()
+        .find_map(|line| line.trim().strip_prefix("**NOTE:**"))
+        .map(|note| note.trim().to_string())
+}
+
+fn split_row(line: &str) -> Row {
+    let inner = line.trim().trim_start_matches('|').trim_end_matches('|');
+    inner
+        .split('|')
+        .map(|cell| cell.trim().replace("**", "").trim().to_string())
+        .collect()
+}
+
+/// Switch number from a heading cell such as "SW8 (S3)"
+fn switch_number(cell: &str) -> Option<usize> {
+    let rest = cell.strip_prefix("SW")?.trim_start();
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+fn parse_switch_table(rows: &[Row]) -> Result<Vec<ManualSwitch>, ManualError> {
+    let mut switches: Vec<ManualSwitch> = Vec::new();
+    // First row is the column header
+    for row in rows.iter().skip(1) {
+        let [number, function, left, right] = row.as_slice() else {
+            return error(format!(
+                "Table 1 row '{}' does not have 4 columns",
+                row.join(" | ")
+            ));
+        };
+        // Rows covering several switches ("6, 7, 8") describe them jointly
+        let Ok(number) = number.parse::<usize>() else {
+            continue;
+        };
+        // Switch 3 is listed twice (SD output, then AES); keep both descriptions
+        if let Some(existing) = switches.iter_mut().find(|s| s.number == number) {
+            existing.left = format!("{} / {}", existing.left, left);
+            existing.right = format!("{} / {}", existing.right, right);
+            continue;
+        }
+        switches.push(ManualSwitch {
+            number,
+            function: function.clone(),
+            left: left.clone(),
+            right: right.clone(),
+        });
+    }
+    if switches.is_empty() {
+        return error("Table 1 describes no switches".to_string());
+    }
+    Ok(switches)
+}
+
+/// Position of a switch whose Table 1 description mentions "(value)"
+fn position_for(
+    switches: &[ManualSwitch],
+    number: usize,
+    value: &str,
+) -> Result<bool, ManualError> {
+    let switch = switches
+        .iter()
+        .find(|switch| switch.number == number)
+        .ok_or_else(|| {
+            ManualError(format!(
+                "Table 2 uses SW{}, which Table 1 does not describe",
+                number
+            ))
+        })?;
+    let label = format!("({})", value);
+    match (switch.left.contains(&label), switch.right.contains(&label)) {
+        (true, false) => Ok(false),
+        (false, true) => Ok(true),
+        _ => error(format!(
+            "Table 2 column '{}' for SW{} does not match exactly one position in Table 1",
+            value, number
+        )),
+    }
+}
+
+fn parse_format_table(
+    rows: &[Row],
+    switches: &[ManualSwitch],
+    note: Option<&str>,
+) -> Result<Vec<ManualEntry>, ManualError> {
+    let is_data = |row: &Row| row.first().is_some_and(|cell| cell == "0" || cell == "1");
+    let (header, data): (Vec<&Row>, Vec<&Row>) = rows.iter().partition(|row| !is_data(row));
+
+    // The row naming several switches in its leading cells labels the bit columns
+    let row_header = header
+        .iter()
+        .find(|row| {
+            row.iter()
+                .take_while(|cell| switch_number(cell).is_some())
+                .count()
+                > 1
+        })
+        .ok_or_else(|| ManualError("Table 2 has no row of switch headings".to_string()))?;
+    let row_switches: Vec<usize> = row_header
+        .iter()
+        .map_while(|cell| switch_number(cell))
+        .collect();
+    let bits = row_switches.len();
+
+    // Remaining heading rows each give one column switch and its values
+    let mut column_switches: Vec<(usize, Vec<String>)> = Vec::new();
+    for row in header
+        .iter()
+        .filter(|row| !std::ptr::eq(**row, *row_header))
+    {
+        let number = row
+            .first()
+            .and_then(|cell| switch_number(cell))
+            .ok_or_else(|| {
+                ManualError(format!(
+                    "Table 2 heading '{}' names no switch",
+                    row.join(" | ")
+                ))
+            })?;
+        let mut values = Vec::new();
+        for cell in row.iter().skip(bits) {
+            // Blank cells continue the value to their left (merged heading)
+            match (cell.is_empty(), values.last()) {
+                (true, Some(previous)) => values.push(String::clone(previous)),
+                (true, None) => return error(format!("Table 2 SW{} heading starts blank", number)),
+                (false, _) => values.push(cell.clone()),
+            }
+        }
+        column_switches.push((number, values));
+    }
+    if bits + column_switches.len() > SWITCHES {
+        return error(format!(
+            "Table 2 headings name {} switches, but the unit has {}",
+            bits + column_switches.len(),
+            SWITCHES
+        ));
+    }
+    let columns = column_switches
+        .first()
+        .map_or(0, |(_, values)| values.len());
+    if columns == 0
+        || column_switches
+            .iter()
+            .any(|(_, values)| values.len() != columns)
+    {
+        return error("Table 2 headings do not span the same columns".to_string());
+    }
+
+    // Switch positions selecting each column, checked to be distinct
+    let mut column_positions: Vec<Vec<(usize, bool)>> = Vec::with_capacity(columns);
+    for column in 0..columns {
+        let positions = column_switches
+            .iter()
+            .map(|(number, values)| {
+                Ok((*number, position_for(switches, *number, &values[column])?))
+            })
+            .collect::<Result<Vec<_>, ManualError>>()?;
+        if column_positions.contains(&positions) {
+            return error(format!(
+                "Table 2 column {} repeats another column",
+                column + 1
+            ));
+        }
+        column_positions.push(positions);
+    }
+    if column_positions.len() != 1 << column_switches.len() {
+        return error("Table 2 does not have a column for every column switch setting".to_string());
+    }
+
+    let note_pairs = note.map(parse_note).transpose()?.unwrap_or_default();
+    let mut row_positions: Vec<Vec<(usize, bool)>> = Vec::new();
+    let mut cells: Vec<Vec<VideoFormat>> = vec![Vec::new(); columns];
+    for row in &data {
+        if row.len() != bits + columns {
+            return error(format!(
+                "Table 2 row '{}' has {} cells, expected {}",
+                row.join(" | "),
+                row.len(),
+                bits + columns
+            ));
+        }
+        let positions: Vec<(usize, bool)> = row_switches
+            .iter()
+            .zip(row.iter())
+            .map(|(&number, cell)| (number, cell == "1"))
+            .collect();
+        if row.iter().take(bits).any(|cell| cell != "0" && cell != "1")
+            || row_positions.contains(&positions)
+        {
+            return error(format!(
+                "Table 2 row '{}' has invalid or repeated switch bits",
+                row.join(" | ")
+            ));
+        }
+        row_positions.push(positions);
+
+        for (column, cell) in row.iter().skip(bits).enumerate() {
+            let format = parse_cell(cell, &note_pairs)?;
+            check_column(format, &column_switches, column)?;
+            cells[column].push(format);
+        }
+    }
+    if row_positions.len() != 1 << bits {
+        return error(format!(
+            "Table 2 has {} rows, expected one for each of the {} switch combinations",
+            row_positions.len(),
+            1 << bits
+        ));
+    }
+
+    let mut entries = Vec::with_capacity(columns * row_positions.len());
+    for (column, formats) in cells.into_iter().enumerate() {
+        for (row, format) in formats.into_iter().enumerate() {
+            let mut positions = column_positions[column].clone();
+            positions.extend(row_positions[row].iter().copied());
+            entries.push(ManualEntry { positions, format });
+        }
+    }
+    Ok(entries)
+}
+
+/// Parse a Table 2 cell: a format, optionally followed by `<br>` and its PsF alias
+fn parse_cell(
+    cell: &str,
+    note_pairs: &[(VideoFormat, VideoFormat)],
+) -> Result<VideoFormat, ManualError> {
+    let mut names = cell.split("<br>").map(str::trim);
+    let name = names.next().unwrap_or_default();
+    let format = match name.parse::<VideoFormat>() {
+        Ok(format) if format.is_hd() => format,
+        _ => return error(format!("Table 2 cell '{}' is not an HD format", name)),
+    };
+
+    let Some(alias) = names.next() else {
+        return Ok(format);
+    };
+    let aliased = format.with_psf_alias();
+    if alias.parse::<VideoFormat>().ok() != aliased.psf_equivalent() {
+        return error(format!(
+            "Table 2 lists '{}' as an alias of '{}', which is not its PsF equivalent",
+            alias, name
+        ));
+    }
+    if !note_pairs
+        .iter()
+        .any(|&(_, interlaced)| interlaced.same_signal(&format))
+    {
+        return error(format!(
+            "Table 2 aliases '{}' as '{}' but the NOTE does not",
+            name, alias
+        ));
+    }
+    Ok(aliased)
+}
+
+/// Parse "For 1080psf29.97 use 1080i59.94, ..." into (psf, interlaced) pairs
+fn parse_note(note: &str) -> Result<Vec<(VideoFormat, VideoFormat)>, ManualError> {
+    let words: Vec<&str> = note
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.trim_end_matches('.'))
+        .collect();
+
+    let mut pairs = Vec::new();
+    for window in words.windows(4) {
+        let [for_word, psf, use_word, interlaced] = window else {
+            continue;
+        };
+        if !for_word.eq_ignore_ascii_case("for") || !use_word.eq_ignore_ascii_case("use") {
+            continue;
+        }
+        let (Ok(psf), Ok(interlaced)) = (
+            psf.parse::<VideoFormat>(),
+            interlaced.parse::<VideoFormat>(),
+        ) else {
+            return error(format!(
+                "cannot read NOTE entry 'for {} use {}'",
+                psf, interlaced
+            ));
+        };
+        if interlaced.with_psf_alias().psf_equivalent() != Some(psf) {
+            return error(format!(
+                "NOTE maps {} to {}, which is not its interlaced equivalent",
+                psf.name(),
+                interlaced.name()
+            ));
+        }
+        pairs.push((psf, interlaced));
+    }
+    Ok(pairs)
+}
+
+/// Check a format against the values heading its column
+fn check_column(
+    format: VideoFormat,
+    column_switches: &[(usize, Vec<String>)],
+    column: usize,
+) -> Result<(), ManualError> {
+    for (number, values) in column_switches {
+        let value = &values[column];
+        let consistent = match value.parse::<u32>() {
+            // Line counts (1080 / 720)
+            Ok(lines) if lines >= 480 => u32::from(format.active_lines) == lines,
+            // Frame rate families (59.94 / 50)
+            _ => match value.parse::<FrameRate>() {
+                Ok(rate) => related_rate(rate, format),
+                Err(_) => true,
+            },
+        };
+        if !consistent {
+            return error(format!(
+                "Table 2 lists {} under SW{} = {}",
+                format.name(),
+                number,
+                value
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a format belongs to the frame rate family named by a column heading
+///
+/// The 59.94 family includes the "true" 24/30/60 rates (see the Table 1 NOTE
+/// for switch 4); the 50 family only contains 25 and 50.
+fn related_rate(family: FrameRate, format: VideoFormat) -> bool {
+    let rate = match format.scan {
+        ScanType::Interlaced | ScanType::SegmentedFrame => match format.frame_rate.times(2) {
+            Some(rate) => rate,
+            None => return false,
+        },
+        ScanType::Progressive => format.frame_rate,
+    };
+    if family.denominator() == 1001 {
+        rate.denominator() == 1001
+            || [24, 30, 48, 60].contains(&rate.numerator()) && rate.denominator() == 1
+    } else {
+        rate.denominator() == 1 && (family.numerator() * 2).is_multiple_of(rate.numerator())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = include_str!("../DIPSWITCHES.md");
+
+    /// Table 2 as transcribed by hand, column by column
+    const TABLE2: [&str; 32] = [
+        "1080i59.94 (1080psf29.97)",
+        "1080psf23.98",
+        "1080p23.98",
+        "1080p29.97",
+        "1080i60 (1080psf30)",
+        "1080psf24",
+        "1080p24",
+        "1080p30",
+        "720p59.94",
+        "720p23.98",
+        "720p23.98",
+        "720p29.97",
+        "720p60",
+        "720p24",
+        "720p24",
+        "720p30",
+        "1080i50",
+        "1080i50",
+        "1080p25",
+        "1080p25",
+        "1080i50 (1080psf25)",
+        "1080i50",
+        "1080p25",
+        "1080p25",
+        "720p50",
+        "720p25",
+        "720p25",
+        "720p25",
+        "720p50",
+        "720p25",
+        "720p25",
+        "720p25",
+    ];
+
+    #[test]
+    fn test_parse_table1() {
+        let manual = Manual::parse(DOC).unwrap();
+        assert_eq!(manual.switches.len(), 5);
+        let sw4 = manual.switch(4).unwrap();
+        assert_eq!(sw4.function, "Frame Rate Format");
+        assert!(sw4.left.contains("(59.94)"));
+        assert!(manual.switch(3).unwrap().right.contains("tone"));
+    }
+
+    #[test]
+    fn test_table2_matches_transcription() {
+        let manual = Manual::parse(DOC).unwrap();
+        let names: Vec<String> = manual.formats.iter().map(|e| e.format.name()).collect();
+        assert_eq!(names, TABLE2);
+
+        // Column 59.94/720, row S3 S2 S1 = 0 1 0
+        let entry = &manual.formats[10];
+        assert_eq!(
+            entry.positions,
+            vec![(4, false), (5, true), (8, false), (7, true), (6, false)]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_format() {
+        let doc = DOC.replace("| 720p29.97 |", "| 720p29.7x |");
+        let err = Manual::parse(&doc).unwrap_err();
+        assert!(err.to_string().contains("720p29.7x"));
+    }
+
+    #[test]
+    fn test_rejects_wrong_family() {
+        let doc = DOC.replace(
+            "| 1080p25 | 720p25 |\n| 0 | 1 | 1 |",
+            "| 1080p30 | 720p25 |\n| 0 | 1 | 1 |",
+        );
+        let err = Manual::parse(&doc).unwrap_err();
+        assert!(
+            err.to_string().contains("1080p30 under SW4 = 50"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_rejects_wrong_line_count() {
+        let doc = DOC.replace("| 720p60 |", "| 1080p60 |");
+        let err = Manual::parse(&doc).unwrap_err();
+        assert!(err.to_string().contains("under SW5 = 720"), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_missing_row() {
+        let doc = DOC.replace("| 1 | 1 | 1 | 1080p30 | 720p30 | 1080p25 | 720p25 |\n", "");
+        let err = Manual::parse(&doc).unwrap_err();
+        assert!(
+            err.to_string().contains("expected one for each of the 8"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_rejects_repeated_row() {
+        let doc = DOC.replace("| 1 | 1 | 1 | 1080p30", "| 1 | 1 | 0 | 1080p30");
+        assert!(Manual::parse(&doc).is_err());
+    }
+
+    #[test]
+    fn test_rejects_alias_missing_from_note() {
+        let doc = DOC.replace(", and for 1080psf25 use 1080i50", "");
+        let err = Manual::parse(&doc).unwrap_err();
+        assert!(err.to_string().contains("NOTE does not"), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_wrong_alias() {
+        let doc = DOC.replace("1080i60<br>1080psf30", "1080i60<br>1080psf24");
+        assert!(Manual::parse(&doc).is_err());
+    }
+
+    #[test]
+    fn test_rejects_too_many_switches() {
+        // 64 row switches, with the column headings moved along to match
+        let padding = "| ".repeat(61);
+        let doc: String = DOC
+            .replace(
+                "| **SW8 (S3)** | **SW7 (S2)** | **SW6 (S1)** |",
+                &format!("{}| | | |", "| SW8 ".repeat(64)),
+            )
+            .replace("| SW4 (FORMAT) ", &format!("| SW4 (FORMAT) {}", padding))
+            .replace(
+                "| **SW5 (HD FMT)** ",
+                &format!("| **SW5 (HD FMT)** {}", padding),
+            )
+            .lines()
+            .filter(|line| !line.starts_with("| 0 ") && !line.starts_with("| 1 "))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let err = Manual::parse(&doc).unwrap_err();
+        assert!(err.to_string().contains("name 66 switches"), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_heading_not_in_table1() {
+        let doc = DOC.replace(
+            "| **SW5 (HD FMT)** | | | **1080** | **720** |",
+            "| **SW5 (HD FMT)** | | | **1080** | **1080i** |",
+        );
+        assert!(Manual::parse(&doc).is_err());
+    }
+}
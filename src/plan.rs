@@ -0,0 +1,179 @@
+//! Toggle plans: the physical switch moves taking a unit from its current
+//! setting to a target setting or output configuration.
+
+use crate::dipswitch::DipSwitch;
+use crate::profile::Profile;
+use crate::solver::{self, ConfigTarget};
+
+/// One physical switch move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwitchMove {
+    /// Switch index (0-based)
+    pub switch: usize,
+    /// Position to move it to (true = RIGHT)
+    pub right: bool,
+}
+
+impl SwitchMove {
+    /// Checklist line for the move, with the position's label if the profile
+    /// has one: "Move SW5 to RIGHT (720)"
+    pub fn describe(&self, profile: &Profile) -> String {
+        let (side, label) = match profile.switches.get(self.switch) {
+            Some(spec) if self.right => ("RIGHT", spec.right.as_deref()),
+            Some(spec) => ("LEFT", spec.left.as_deref()),
+            None => (if self.right { "RIGHT" } else { "LEFT" }, None),
+        };
+        match label {
+            Some(label) => format!("Move SW{} to {} ({})", self.switch + 1, side, label),
+            None => format!("Move SW{} to {}", self.switch + 1, side),
+        }
+    }
+}
+
+/// How to pick the target among equivalent settings of a configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanChoice {
+    /// The solver's preferred (canonical) setting
+    Canonical,
+    /// Whichever equivalent setting needs the fewest moves, the canonical
+    /// one on a tie
+    FewestMoves,
+}
+
+/// Ordered switch moves from one setting to another
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TogglePlan {
+    pub from: DipSwitch,
+    pub to: DipSwitch,
+    /// One move per differing switch, in switch order; no plan between the
+    /// two settings has fewer
+    pub moves: Vec<SwitchMove>,
+    /// Number of settings that produce the same configuration as `to`
+    /// (1 when the target was a setting)
+    pub equivalents: usize,
+}
+
+impl TogglePlan {
+    /// Plan the moves from `from` to the setting `to`
+    pub fn between(profile: &Profile, from: &DipSwitch, to: &DipSwitch) -> TogglePlan {
+        let moves = (0..profile.switch_count())
+            .filter(|&switch| from.get(switch) != to.get(switch))
+            .map(|switch| SwitchMove {
+                switch,
+                right: to.get(switch),
+            })
+            .collect();
+        TogglePlan {
+            from: from.clone(),
+            to: to.clone(),
+            moves,
+            equivalents: 1,
+        }
+    }
+
+    /// Plan the moves from `from` to a setting producing `target`, or `None`
+    /// if the unit cannot produce it
+    pub fn to_config(
+        profile: &Profile,
+        from: &DipSwitch,
+        target: &ConfigTarget,
+        choice: PlanChoice,
+    ) -> Option<TogglePlan> {
+        let solutions = solver::solve_with(profile, target);
+        let best = match choice {
+            PlanChoice::Canonical => solutions.first(),
+            PlanChoice::FewestMoves => solutions.iter().min_by_key(|solution| {
+                TogglePlan::between(profile, from, &solution.dipswitch)
+                    .moves
+                    .len()
+            }),
+        }?;
+        Some(TogglePlan {
+            equivalents: solutions.len(),
+            ..TogglePlan::between(profile, from, &best.dipswitch)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Setting after the first `done` moves
+    pub fn setting_after(&self, done: usize) -> DipSwitch {
+        let mut dipswitch = self.from.clone();
+        for step in self.moves.iter().take(done) {
+            dipswitch.set(step.switch, step.right);
+        }
+        dipswitch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::GroupTarget;
+
+    fn setting(positions: &str) -> DipSwitch {
+        positions.parse().unwrap()
+    }
+
+    #[test]
+    fn test_between() {
+        let profile = Profile::gen10();
+        let plan = TogglePlan::between(profile, &setting("RRLLLLLL"), &setting("RRLRRLLL"));
+        let lines: Vec<String> = plan.moves.iter().map(|m| m.describe(profile)).collect();
+        assert_eq!(lines, ["Move SW4 to RIGHT (50)", "Move SW5 to RIGHT (720)"]);
+        assert_eq!(plan.setting_after(1), setting("RRLRLLLL"));
+        assert_eq!(plan.setting_after(2), plan.to);
+
+        // Unlabelled positions have no label in the checklist
+        let plan = TogglePlan::between(profile, &setting("LLLLLLLR"), &setting("LLLLLLLL"));
+        assert_eq!(plan.moves[0].describe(profile), "Move SW8 to LEFT");
+        assert!(TogglePlan::between(profile, &plan.to, &plan.to).is_empty());
+    }
+
+    #[test]
+    fn test_to_config_fewest_moves() {
+        let profile = Profile::gen10();
+        let p25: GroupTarget = "720p25".parse().unwrap();
+        let target = ConfigTarget {
+            group1: p25,
+            group2: p25,
+            sd_video: None,
+        };
+        // 720p25 is listed in six Table 2 cells; from S1-S3 all RIGHT the
+        // nearest one keeps them
+        let from = setting("RRLRRRRR");
+        let canonical =
+            TogglePlan::to_config(profile, &from, &target, PlanChoice::Canonical).unwrap();
+        let fewest =
+            TogglePlan::to_config(profile, &from, &target, PlanChoice::FewestMoves).unwrap();
+        assert_eq!(fewest.equivalents, canonical.equivalents);
+        assert!(fewest.equivalents > 1);
+        assert!(fewest.moves.len() < canonical.moves.len());
+        assert!(target.matches(&profile.config(&fewest.to)));
+
+        // Already producing the target: nothing to move
+        let already =
+            TogglePlan::to_config(profile, &fewest.to, &target, PlanChoice::FewestMoves).unwrap();
+        assert!(already.is_empty());
+    }
+
+    #[test]
+    fn test_to_config_impossible() {
+        let target = ConfigTarget {
+            group1: "1080p60".parse().unwrap(),
+            group2: GroupTarget::Sd,
+            sd_video: None,
+        };
+        assert!(
+            TogglePlan::to_config(
+                Profile::gen10(),
+                &DipSwitch::new(),
+                &target,
+                PlanChoice::FewestMoves
+            )
+            .is_none()
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Named presets of full switch settings, kept in a TOML file under the user's
+//! XDG config directory (`$XDG_CONFIG_HOME/gen10ds/presets.toml`, falling
+//! back to `~/.config/gen10ds/presets.toml`):
+//!
+//! ```toml
+//! [[preset]]
+//! name = "Studio 1080i59.94"
+//! switches = "RRLLLLLL"
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, ConfigError};
+use crate::dipswitch::DipSwitch;
+
+/// File name of the preset store inside the config directory
+const PRESET_FILE: &str = "gen10ds/presets.toml";
+
+/// A named switch setting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    pub dipswitch: DipSwitch,
+}
+
+/// Presets in file order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PresetStore {
+    presets: Vec<Preset>,
+}
+
+/// Preset file as written on disk
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawPresetFile {
+    #[serde(default, rename = "preset")]
+    presets: Vec<RawPreset>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RawPreset {
+    name: String,
+    /// Positions with switch 1 first, e.g. "LRRLLRLL"
+    switches: String,
+}
+
+impl PresetStore {
+    /// Location of the user's preset file, if a config directory is known
+    pub fn default_path() -> Option<PathBuf> {
+        config::user_config_file(PRESET_FILE)
+    }
+
+    /// Read presets from `path`; a missing file is an empty store
+    pub fn load(path: impl AsRef<Path>) -> Result<PresetStore, ConfigError> {
+        match config::read_optional(path)? {
+            Some(source) => PresetStore::from_toml_str(&source),
+            None => Ok(PresetStore::default()),
+        }
+    }
+
+    pub fn from_toml_str(source: &str) -> Result<PresetStore, ConfigError> {
+        let raw: RawPresetFile =
+            toml::from_str(source).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        let mut store = PresetStore::default();
+        for preset in raw.presets {
+            let dipswitch: DipSwitch = preset.switches.parse().map_err(|err| {
+                ConfigError::Invalid(format!("invalid preset '{}': {}", preset.name, err))
+            })?;
+            if store.get(&preset.name).is_some() {
+                return Err(ConfigError::Invalid(format!(
+                    "preset '{}' is listed twice",
+                    preset.name
+                )));
+            }
+            store.insert(&preset.name, dipswitch)?;
+        }
+        Ok(store)
+    }
+
+    pub fn to_toml_string(&self) -> String {
+        let raw = RawPresetFile {
+            presets: self
+                .presets
+                .iter()
+                .map(|preset| RawPreset {
+                    name: preset.name.clone(),
+                    switches: preset.dipswitch.to_string(),
+                })
+                .collect(),
+        };
+        toml::to_string(&raw).expect("presets serialize to TOML")
+    }
+
+    /// Write the presets to `path`, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml_string())?;
+        Ok(())
+    }
+
+    pub fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+
+    /// Preset with the given name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.position(name).map(|index| &self.presets[index])
+    }
+
+    /// Add a preset, replacing one with the same name in place; returns
+    /// whether a preset was replaced
+    pub fn insert(&mut self, name: &str, dipswitch: DipSwitch) -> Result<bool, ConfigError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ConfigError::Invalid("the preset name is empty".to_string()));
+        }
+        let preset = Preset {
+            name: name.to_string(),
+            dipswitch,
+        };
+        match self.position(name) {
+            Some(index) => {
+                self.presets[index] = preset;
+                Ok(true)
+            }
+            None => {
+                self.presets.push(preset);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Remove the preset with the given name, ignoring case
+    pub fn remove(&mut self, name: &str) -> Option<Preset> {
+        self.position(name).map(|index| self.presets.remove(index))
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        let name = name.trim();
+        self.presets
+            .iter()
+            .position(|preset| preset.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORE: &str = r#"
+[[preset]]
+name = "Studio 1080i59.94"
+switches = "RRLLLLLL"
+
+[[preset]]
+name = "OB truck 720p50"
+switches = "RRLRRLLL"
+"#;
+
+    #[test]
+    fn test_parse_and_lookup() {
+        let store = PresetStore::from_toml_str(STORE).unwrap();
+        assert_eq!(store.presets().len(), 2);
+        let truck = store.get("ob TRUCK 720p50").unwrap();
+        assert_eq!(truck.dipswitch.to_string(), "RRLRRLLL");
+        assert!(store.get("missing").is_none());
+        assert!(PresetStore::from_toml_str("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let store = PresetStore::from_toml_str(STORE).unwrap();
+        let written = store.to_toml_string();
+        assert_eq!(PresetStore::from_toml_str(&written).unwrap(), store);
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut store = PresetStore::from_toml_str(STORE).unwrap();
+        let setting: DipSwitch = "LLLLLLLR".parse().unwrap();
+        assert!(!store.insert(" Edit bay ", setting.clone()).unwrap());
+        assert_eq!(store.presets()[2].name, "Edit bay");
+        // Same name, any case: replaced in place
+        assert!(store.insert("studio 1080i59.94", setting.clone()).unwrap());
+        assert_eq!(store.presets()[0].dipswitch, setting);
+        assert_eq!(store.presets().len(), 3);
+        assert!(store.insert("  ", setting).is_err());
+
+        assert_eq!(store.remove("edit bay").unwrap().name, "Edit bay");
+        assert!(store.remove("edit bay").is_none());
+    }
+
+    #[test]
+    fn test_invalid_store() {
+        let bad_switches = "[[preset]]\nname = \"x\"\nswitches = \"RRL\"\n";
+        assert!(matches!(
+            PresetStore::from_toml_str(bad_switches),
+            Err(ConfigError::Invalid(_))
+        ));
+        let twice = format!(
+            "{}{}",
+            bad_switches.replace("RRL", "RRLLLLLL"),
+            bad_switches.replace("RRL", "LLLLLLLL")
+        );
+        assert!(matches!(
+            PresetStore::from_toml_str(&twice),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            PresetStore::from_toml_str("[[preset]]\nname = \"x\"\n"),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,746 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::dipswitch::{AudioOutput, DipSwitch, OutputConfig, ParseDipSwitchError, SdVideo};
+use crate::manual::Manual;
+use crate::video_format::VideoFormat;
+
+/// Source of the built-in GEN10 profile
+const GEN10_TOML: &str = include_str!("../profiles/gen10.toml");
+
+/// Manual excerpt the built-in profile takes its format table from
+const GEN10_MANUAL: &str = include_str!("../DIPSWITCHES.md");
+
+/// Largest switch bank a profile may describe (one raw `u8`)
+pub const MAX_SWITCHES: usize = 8;
+
+/// Error returned when a device profile cannot be loaded
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(io::Error),
+    Parse(String),
+    Invalid(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Io(err) => write!(f, "cannot read profile: {}", err),
+            ProfileError::Parse(message) => write!(f, "cannot parse profile: {}", message),
+            ProfileError::Invalid(message) => write!(f, "invalid profile: {}", message),
+        }
+    }
+}
+
+impl Error for ProfileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProfileError::Io(err) => Some(err),
+            ProfileError::Parse(_) | ProfileError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ProfileError {
+    fn from(err: io::Error) -> Self {
+        ProfileError::Io(err)
+    }
+}
+
+/// Labels of a single switch as printed on the unit
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SwitchSpec {
+    /// Short function label shown under the switch (e.g. "OUT 1-4")
+    pub function: String,
+    /// Longer description from the manual
+    #[serde(default)]
+    pub description: String,
+    /// Meaning of the LEFT position, if labelled
+    pub left: Option<String>,
+    /// Meaning of the RIGHT position, if labelled
+    pub right: Option<String>,
+}
+
+/// Description of a sync generator's switch bank and format lookup table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub switches: Vec<SwitchSpec>,
+    /// Switch (0-based) selecting SD or HD sync for each output group
+    pub group_sync: [usize; 2],
+    /// Switch (0-based) selecting SD output and audio
+    pub sd_output: usize,
+    /// Switch (0-based) selecting between the two SD formats
+    pub sd_format: usize,
+    /// Switches (0-based) indexing the HD format table, bit 0 first
+    pub format_table: Vec<usize>,
+    /// SD formats for the LEFT and RIGHT position of `sd_format`
+    pub sd_formats: [VideoFormat; 2],
+    /// HD formats indexed by the `format_table` switch positions
+    formats: Vec<VideoFormat>,
+    /// Table entries in the order the profile lists them
+    table_order: Vec<usize>,
+}
+
+/// Profile file layout, converted and validated into a `Profile`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawProfile {
+    name: String,
+    switch: Vec<SwitchSpec>,
+    roles: RawRoles,
+    sd_formats: RawSdFormats,
+    /// Manual excerpt (relative to the profile) holding Table 1 and Table 2
+    formats_from: Option<String>,
+    formats: Option<toml::Table>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRoles {
+    group_sync: Vec<usize>,
+    sd_output: usize,
+    sd_format: usize,
+    format_table: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSdFormats {
+    left: String,
+    right: String,
+}
+
+impl Profile {
+    /// The built-in AJA GEN10 profile
+    pub fn gen10() -> &'static Profile {
+        static GEN10: OnceLock<Profile> = OnceLock::new();
+        GEN10.get_or_init(|| {
+            let profile = Profile::from_toml_with(GEN10_TOML, |name| match name {
+                "../DIPSWITCHES.md" => Ok(GEN10_MANUAL.to_string()),
+                _ => Err(ProfileError::Invalid(format!(
+                    "no built-in manual '{}'",
+                    name
+                ))),
+            });
+            profile.unwrap_or_else(|err| panic!("built-in GEN10 profile: {}", err))
+        })
+    }
+
+    /// Load a profile from a TOML file
+    ///
+    /// A `formats_from` manual is read relative to the profile's directory.
+    pub fn load(path: impl AsRef<Path>) -> Result<Profile, ProfileError> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or(Path::new(""));
+        Profile::from_toml_with(&fs::read_to_string(path)?, |name| {
+            Ok(fs::read_to_string(dir.join(name))?)
+        })
+    }
+
+    /// Parse and validate a profile from TOML source
+    ///
+    /// A `formats_from` manual is read relative to the current directory.
+    pub fn from_toml_str(source: &str) -> Result<Profile, ProfileError> {
+        Profile::from_toml_with(source, |name| Ok(fs::read_to_string(name)?))
+    }
+
+    fn from_toml_with(
+        source: &str,
+        read_manual: impl Fn(&str) -> Result<String, ProfileError>,
+    ) -> Result<Profile, ProfileError> {
+        let raw: RawProfile =
+            toml::from_str(source).map_err(|err| ProfileError::Parse(err.to_string()))?;
+        let manual = match &raw.formats_from {
+            Some(name) => {
+                let manual = Manual::parse(&read_manual(name)?)
+                    .map_err(|err| ProfileError::Invalid(format!("{}: {}", name, err)))?;
+                Some(manual)
+            }
+            None => None,
+        };
+        Profile::from_raw(raw, manual.as_ref())
+    }
+
+    fn from_raw(raw: RawProfile, manual: Option<&Manual>) -> Result<Profile, ProfileError> {
+        let invalid = |message: String| Err(ProfileError::Invalid(message));
+        let count = raw.switch.len();
+        if count == 0 || count > MAX_SWITCHES {
+            return invalid(format!(
+                "expected 1 to {} switches, found {}",
+                MAX_SWITCHES, count
+            ));
+        }
+
+        // Switch numbers in the file are 1-based as printed on the unit
+        let index = |number: usize, role: &str| {
+            if (1..=count).contains(&number) {
+                Ok(number - 1)
+            } else {
+                Err(ProfileError::Invalid(format!(
+                    "{} refers to switch {}, but there are {} switches",
+                    role, number, count
+                )))
+            }
+        };
+
+        let group_sync = match raw.roles.group_sync.as_slice() {
+            &[group1, group2] => [index(group1, "group_sync")?, index(group2, "group_sync")?],
+            other => {
+                return invalid(format!(
+                    "group_sync must list one switch for each of the 2 output groups, found {}",
+                    other.len()
+                ));
+            }
+        };
+        let sd_output = index(raw.roles.sd_output, "sd_output")?;
+        let sd_format = index(raw.roles.sd_format, "sd_format")?;
+        let format_table = raw
+            .roles
+            .format_table
+            .iter()
+            .map(|&number| index(number, "format_table"))
+            .collect::<Result<Vec<_>, _>>()?;
+        if format_table.is_empty() {
+            return invalid("format_table must list at least one switch".to_string());
+        }
+        if (1..format_table.len()).any(|i| format_table[i..].contains(&format_table[i - 1])) {
+            return invalid("format_table lists a switch more than once".to_string());
+        }
+
+        let parse_sd = |name: &str| match name.parse::<VideoFormat>() {
+            Ok(format) if !format.is_hd() => Ok(format),
+            _ => Err(ProfileError::Invalid(format!(
+                "'{}' is not an SD format",
+                name
+            ))),
+        };
+        let sd_formats = [
+            parse_sd(&raw.sd_formats.left)?,
+            parse_sd(&raw.sd_formats.right)?,
+        ];
+
+        let mut formats: Vec<Option<VideoFormat>> = vec![None; 1 << format_table.len()];
+        let mut table_order = Vec::with_capacity(formats.len());
+        let mut add_format = |entry: usize, format: VideoFormat| {
+            if formats[entry].replace(format).is_some() {
+                return Err(ProfileError::Invalid(format!(
+                    "format for switch positions '{}' is listed twice",
+                    table_key(entry, format_table.len())
+                )));
+            }
+            table_order.push(entry);
+            Ok(())
+        };
+        match (&raw.formats, manual) {
+            (Some(table), None) => {
+                for (key, name) in table {
+                    let Some(name) = name.as_str() else {
+                        return invalid(format!("format '{}' must be a string", key));
+                    };
+                    let entry = table_index(key, format_table.len()).ok_or_else(|| {
+                        ProfileError::Invalid(format!(
+                            "format key '{}' must be {} L/R positions",
+                            key,
+                            format_table.len()
+                        ))
+                    })?;
+                    let format = match name.parse::<VideoFormat>() {
+                        Ok(format) if format.is_hd() => format,
+                        _ => return invalid(format!("'{}' ({}) is not an HD format", name, key)),
+                    };
+                    add_format(entry, format)?;
+                }
+            }
+            (None, Some(manual)) => {
+                check_manual_labels(&raw.switch, manual)?;
+                for manual_entry in &manual.formats {
+                    let entry = manual_table_entry(&format_table, &manual_entry.positions)?;
+                    add_format(entry, manual_entry.format)?;
+                }
+            }
+            _ => return invalid("expected exactly one of [formats] and formats_from".to_string()),
+        }
+        let formats = formats
+            .into_iter()
+            .enumerate()
+            .map(|(entry, format)| {
+                format.ok_or_else(|| {
+                    ProfileError::Invalid(format!(
+                        "no format for switch positions '{}'",
+                        table_key(entry, format_table.len())
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Profile {
+            name: raw.name,
+            switches: raw.switch,
+            group_sync,
+            sd_output,
+            sd_format,
+            format_table,
+            sd_formats,
+            formats,
+            table_order,
+        })
+    }
+
+    /// Number of switches on the unit
+    pub fn switch_count(&self) -> usize {
+        self.switches.len()
+    }
+
+    /// Name of an output group (0-based) with the outputs its sync switch
+    /// is labelled with, e.g. "Group 1 (Out 1-4)"
+    pub fn group_label(&self, group: usize) -> String {
+        let function = &self.switches[self.group_sync[group]].function;
+        let words: Vec<String> = function
+            .split_whitespace()
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| format!("{}{}", first, chars.as_str().to_lowercase()))
+                    .unwrap_or_default()
+            })
+            .collect();
+        format!("Group {} ({})", group + 1, words.join(" "))
+    }
+
+    /// Raw bits of the switches that select the HD format
+    pub fn format_mask(&self) -> u8 {
+        self.format_table
+            .iter()
+            .fold(0, |mask, &index| mask | (1 << index))
+    }
+
+    /// Look up the HD format for the given `format_table` switch positions
+    pub fn hd_format(&self, positions: &[bool]) -> VideoFormat {
+        let entry = positions
+            .iter()
+            .take(self.format_table.len())
+            .enumerate()
+            .fold(0, |entry, (bit, &right)| entry | ((right as usize) << bit));
+        self.formats[entry]
+    }
+
+    /// HD format of a table entry (bit `i` is the position of switch
+    /// `format_table[i]`)
+    pub fn hd_format_entry(&self, entry: usize) -> VideoFormat {
+        self.formats[entry]
+    }
+
+    /// HD format table entries in profile order, as (entry, format) where bit
+    /// `i` of the entry is the position of switch `format_table[i]`
+    pub fn table_entries(&self) -> impl Iterator<Item = (usize, VideoFormat)> + '_ {
+        self.table_order
+            .iter()
+            .map(|&entry| (entry, self.formats[entry]))
+    }
+
+    /// Move the `format_table` switches into the positions of a table entry
+    pub fn apply_table_entry(&self, dipswitch: &mut DipSwitch, entry: usize) {
+        for (bit, &index) in self.format_table.iter().enumerate() {
+            dipswitch.set(index, entry & (1 << bit) != 0);
+        }
+    }
+
+    /// Switches (0-based) heading the columns of the format table grid, laid
+    /// out like the manual's Table 2 (SW4 and SW5 on the GEN10)
+    pub fn table_column_switches(&self) -> &[usize] {
+        &self.format_table[..self.format_table.len().min(2)]
+    }
+
+    /// Switches (0-based) numbering the rows of the format table grid, least
+    /// significant first (S1, S2, S3 on the GEN10)
+    pub fn table_row_switches(&self) -> &[usize] {
+        &self.format_table[self.table_column_switches().len()..]
+    }
+
+    /// Table entry at a cell of the grid: columns count with the first column
+    /// switch most significant (LEFT before RIGHT), rows count in binary
+    pub fn table_grid_entry(&self, row: usize, column: usize) -> usize {
+        let columns = self.table_column_switches().len();
+        let column_bits =
+            (0..columns).fold(0, |bits, i| bits | ((column >> (columns - 1 - i)) & 1) << i);
+        column_bits | row << columns
+    }
+
+    /// Grid cell (row, column) of a table entry
+    pub fn table_grid_cell(&self, entry: usize) -> (usize, usize) {
+        let columns = self.table_column_switches().len();
+        let column = (0..columns).fold(0, |column, i| {
+            column | ((entry >> i) & 1) << (columns - 1 - i)
+        });
+        (entry >> columns, column)
+    }
+
+    /// Table entry selected by the `format_table` switches of a setting
+    pub fn table_entry(&self, dipswitch: &DipSwitch) -> usize {
+        self.format_table
+            .iter()
+            .enumerate()
+            .fold(0, |entry, (bit, &index)| {
+                entry | (dipswitch.get(index) as usize) << bit
+            })
+    }
+
+    /// Format produced by an output group (0 = first group) for a switch setting
+    pub fn group_format(&self, dipswitch: &DipSwitch, group: usize) -> VideoFormat {
+        if !dipswitch.get(self.group_sync[group]) {
+            return self.sd_formats[dipswitch.get(self.sd_format) as usize];
+        }
+        let positions: Vec<bool> = self
+            .format_table
+            .iter()
+            .map(|&index| dipswitch.get(index))
+            .collect();
+        self.hd_format(&positions)
+    }
+
+    /// Calculate the complete output configuration for a switch setting
+    pub fn config(&self, dipswitch: &DipSwitch) -> OutputConfig {
+        let (sd_video, audio) = if dipswitch.get(self.sd_output) {
+            (SdVideo::ColorBars75, AudioOutput::Tone)
+        } else {
+            (SdVideo::ColorBlack, AudioOutput::Silent)
+        };
+
+        OutputConfig {
+            group1_format: self.group_format(dipswitch, 0),
+            group2_format: self.group_format(dipswitch, 1),
+            sd_format: self.sd_formats[dipswitch.get(self.sd_format) as usize],
+            sd_video,
+            audio,
+        }
+    }
+
+    /// Every switch setting that exists on this unit
+    pub fn all_settings(&self) -> impl Iterator<Item = DipSwitch> + use<> {
+        (0..1u16 << self.switch_count()).map(|raw| DipSwitch::from_raw(raw as u8))
+    }
+
+    /// Position string for the switches on this unit (e.g. "LRRLLRLL")
+    pub fn positions(&self, dipswitch: &DipSwitch) -> String {
+        let mut positions = dipswitch.to_string();
+        positions.truncate(self.switch_count());
+        positions
+    }
+
+    /// Parse a switch setting for this unit: a position string with one L/R
+    /// per switch, or a raw hex ("0x1B") or binary ("0b00011011") value
+    pub fn parse_switches(&self, s: &str) -> Result<DipSwitch, ParseDipSwitchError> {
+        let s = s.trim();
+        let count = self.switch_count();
+        let lower = s.to_ascii_lowercase();
+        if lower.starts_with("0x") || lower.starts_with("0b") {
+            let dipswitch: DipSwitch = s.parse()?;
+            if (dipswitch.to_raw() as u16) >> count != 0 {
+                return Err(ParseDipSwitchError::new(format!(
+                    "raw value '{}' sets switches the {} does not have",
+                    s, self.name
+                )));
+            }
+            return Ok(dipswitch);
+        }
+
+        if s.chars().count() != count {
+            return Err(ParseDipSwitchError::new(format!(
+                "expected {} switch positions (L/R), got '{}'",
+                count, s
+            )));
+        }
+        format!("{:L<width$}", s, width = MAX_SWITCHES).parse()
+    }
+}
+
+/// Table entry for manual switch positions, which must name exactly the
+/// `format_table` switches
+fn manual_table_entry(
+    format_table: &[usize],
+    positions: &[(usize, bool)],
+) -> Result<usize, ProfileError> {
+    let mut entry = 0;
+    for &(number, right) in positions {
+        let bit = format_table
+            .iter()
+            .position(|&index| index + 1 == number)
+            .ok_or_else(|| {
+                ProfileError::Invalid(format!(
+                    "manual Table 2 uses SW{}, which is not in format_table",
+                    number
+                ))
+            })?;
+        entry |= (right as usize) << bit;
+    }
+    if positions.len() != format_table.len() {
+        return Err(ProfileError::Invalid(format!(
+            "manual Table 2 uses {} switches, but format_table lists {}",
+            positions.len(),
+            format_table.len()
+        )));
+    }
+    Ok(entry)
+}
+
+/// Check switch descriptions and position labels against manual Table 1
+fn check_manual_labels(switches: &[SwitchSpec], manual: &Manual) -> Result<(), ProfileError> {
+    for (index, spec) in switches.iter().enumerate() {
+        let Some(described) = manual.switch(index + 1) else {
+            continue;
+        };
+        let mismatch = |what: &str, label: &str| {
+            Err(ProfileError::Invalid(format!(
+                "switch {} {} '{}' does not match manual Table 1",
+                index + 1,
+                what,
+                label
+            )))
+        };
+        if !spec.description.is_empty() && spec.description != described.function {
+            return mismatch("description", &spec.description);
+        }
+        for (what, label, text) in [
+            ("LEFT label", &spec.left, &described.left),
+            ("RIGHT label", &spec.right, &described.right),
+        ] {
+            if let Some(label) = label
+                && !text.contains(&format!("({})", label))
+            {
+                return mismatch(what, label);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Table entry for a key such as "LRRLL" (first character = bit 0)
+fn table_index(key: &str, len: usize) -> Option<usize> {
+    if key.chars().count() != len {
+        return None;
+    }
+    key.chars()
+        .enumerate()
+        .try_fold(0, |entry, (bit, c)| match c.to_ascii_uppercase() {
+            'L' => Some(entry),
+            'R' => Some(entry | (1 << bit)),
+            _ => None,
+        })
+}
+
+/// Key string for a table entry, the inverse of `table_index`
+fn table_key(entry: usize, len: usize) -> String {
+    (0..len)
+        .map(|bit| if entry & (1 << bit) != 0 { 'R' } else { 'L' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_format::{FrameRate, ScanType};
+
+    const MINIMAL: &str = r#"
+        name = "Mini"
+
+        [[switch]]
+        function = "SYNC"
+        left = "SD"
+        right = "HD"
+
+        [[switch]]
+        function = "RATE"
+        left = "59.94"
+        right = "50"
+
+        [roles]
+        group_sync = [1, 1]
+        sd_output = 2
+        sd_format = 2
+        format_table = [2]
+
+        [sd_formats]
+        left = "525i"
+        right = "625i"
+
+        [formats]
+        L = "1080i59.94"
+        R = "1080i50"
+    "#;
+
+    #[test]
+    fn test_builtin_gen10() {
+        let profile = Profile::gen10();
+        assert_eq!(profile.name, "GEN10");
+        assert_eq!(profile.switch_count(), 8);
+        assert_eq!(profile.switches[4].right.as_deref(), Some("720"));
+        assert_eq!(profile.format_mask(), 0b1111_1000);
+        assert_eq!(profile.group_label(0), "Group 1 (Out 1-4)");
+        assert_eq!(profile.group_label(1), "Group 2 (Out 5-6)");
+        assert_eq!(
+            profile.hd_format(&[false, false, false, false, true]),
+            VideoFormat::hd(1080, ScanType::Interlaced, FrameRate::FPS_30).with_psf_alias()
+        );
+    }
+
+    #[test]
+    fn test_minimal_profile() {
+        let profile = Profile::from_toml_str(MINIMAL).unwrap();
+        assert_eq!(profile.switch_count(), 2);
+        assert_eq!(profile.all_settings().count(), 4);
+        assert_eq!(profile.group_label(1), "Group 2 (Sync)");
+
+        let config = profile.config(&profile.parse_switches("RR").unwrap());
+        assert_eq!(config.group1_format.to_string(), "HD - 1080i50");
+        assert_eq!(config.sd_video, SdVideo::ColorBars75);
+
+        let config = profile.config(&profile.parse_switches("LR").unwrap());
+        assert_eq!(config.group2_format, VideoFormat::sd_625());
+    }
+
+    #[test]
+    fn test_parse_switches() {
+        let profile = Profile::from_toml_str(MINIMAL).unwrap();
+        assert_eq!(profile.parse_switches("RL").unwrap().to_raw(), 0b01);
+        assert_eq!(profile.parse_switches("0x02").unwrap().to_raw(), 0b10);
+        assert!(profile.parse_switches("RLL").is_err());
+        assert!(profile.parse_switches("0x04").is_err());
+        assert_eq!(profile.positions(&DipSwitch::from_raw(0b10)), "LR");
+    }
+
+    #[test]
+    fn test_table_entries_in_profile_order() {
+        let profile = Profile::gen10();
+        let entries: Vec<_> = profile.table_entries().collect();
+        assert_eq!(entries.len(), 32);
+        assert_eq!(entries[0].1.to_string(), "HD - 1080i59.94 (1080psf29.97)");
+        assert_eq!(entries[1].1.to_string(), "HD - 1080psf23.98");
+
+        let mut dipswitch = DipSwitch::new();
+        profile.apply_table_entry(&mut dipswitch, entries[1].0);
+        assert_eq!(dipswitch.to_string(), "LLLLLRLL");
+    }
+
+    #[test]
+    fn test_missing_table_entry() {
+        let source = MINIMAL.replace("R = \"1080i50\"", "");
+        let err = Profile::from_toml_str(&source).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("no format for switch positions 'R'")
+        );
+    }
+
+    #[test]
+    fn test_invalid_profiles() {
+        for (from, to) in [
+            ("group_sync = [1, 1]", "group_sync = [1, 3]"),
+            ("group_sync = [1, 1]", "group_sync = [1]"),
+            ("format_table = [2]", "format_table = [2, 2]"),
+            ("R = \"1080i50\"", "RL = \"1080i50\""),
+            ("R = \"1080i50\"", "R = \"625i\""),
+            ("left = \"525i\"", "left = \"720p50\""),
+        ] {
+            let source = MINIMAL.replace(from, to);
+            assert!(
+                matches!(
+                    Profile::from_toml_str(&source),
+                    Err(ProfileError::Invalid(_))
+                ),
+                "{} should be rejected",
+                to
+            );
+        }
+        assert!(matches!(
+            Profile::from_toml_str("name = 3"),
+            Err(ProfileError::Parse(_))
+        ));
+    }
+
+    fn gen10_with_manual(source: &str, manual: &str) -> Result<Profile, ProfileError> {
+        let manual = manual.to_string();
+        Profile::from_toml_with(source, |_| Ok(manual.clone()))
+    }
+
+    #[test]
+    fn test_builtin_table_matches_manual() {
+        let manual = Manual::parse(GEN10_MANUAL).unwrap();
+        let profile = Profile::gen10();
+        let formats: Vec<VideoFormat> = profile.table_entries().map(|(_, f)| f).collect();
+        let expected: Vec<VideoFormat> = manual.formats.iter().map(|e| e.format).collect();
+        assert_eq!(formats, expected);
+    }
+
+    #[test]
+    fn test_manual_mismatch_fails() {
+        let label = GEN10_TOML.replace("right = \"720\"", "right = \"1080\"");
+        let err = gen10_with_manual(&label, GEN10_MANUAL).unwrap_err();
+        assert!(err.to_string().contains("switch 5 RIGHT label"), "{}", err);
+
+        let table = GEN10_MANUAL.replace("| 720p24 | 1080p25 |", "| 720p24 | 1080p24 |");
+        let err = gen10_with_manual(GEN10_TOML, &table).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("invalid profile: ../DIPSWITCHES.md:"),
+            "{}",
+            err
+        );
+
+        let roles = GEN10_TOML.replace(
+            "format_table = [4, 5, 6, 7, 8]",
+            "format_table = [4, 5, 6, 7]",
+        );
+        assert!(gen10_with_manual(&roles, GEN10_MANUAL).is_err());
+    }
+
+    #[test]
+    fn test_formats_source_required_once() {
+        let both = format!("formats_from = \"DIPSWITCHES.md\"\n{}", MINIMAL);
+        assert!(gen10_with_manual(&both, GEN10_MANUAL).is_err());
+        let neither = MINIMAL.replace(
+            "[formats]\n        L = \"1080i59.94\"\n        R = \"1080i50\"",
+            "",
+        );
+        assert!(Profile::from_toml_str(&neither).is_err());
+    }
+
+    #[test]
+    fn test_table_grid_matches_manual_layout() {
+        let profile = Profile::gen10();
+        assert_eq!(profile.table_column_switches(), &[3, 4]);
+        assert_eq!(profile.table_row_switches(), &[5, 6, 7]);
+        let cell = |row, column| profile.formats[profile.table_grid_entry(row, column)].name();
+        // Columns 59.94/1080, 59.94/720, 50/1080, 50/720; rows S3 S2 S1 = 000..111
+        assert_eq!(cell(0, 0), "1080i59.94 (1080psf29.97)");
+        assert_eq!(cell(0, 1), "720p59.94");
+        assert_eq!(cell(0, 2), "1080i50");
+        assert_eq!(cell(1, 0), "1080psf23.98");
+        assert_eq!(cell(6, 0), "1080p24");
+        assert_eq!(cell(7, 3), "720p25");
+
+        for entry in 0..32 {
+            let (row, column) = profile.table_grid_cell(entry);
+            assert_eq!(profile.table_grid_entry(row, column), entry);
+        }
+        let dipswitch: DipSwitch = "RLLRRLRL".parse().unwrap();
+        assert_eq!(
+            profile.table_grid_cell(profile.table_entry(&dipswitch)),
+            (2, 3)
+        );
+    }
+
+    #[test]
+    fn test_table_key_round_trip() {
+        for entry in 0..32 {
+            assert_eq!(table_index(&table_key(entry, 5), 5), Some(entry));
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! The SD test frame selected by the SD output switch, rendered with ITU-R
+//! BT.601 values and written as a PPM/PNG preview or raw 10-bit 4:2:2 video.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::dipswitch::SdVideo;
+use crate::video_format::VideoFormat;
+
+/// Active samples per line of BT.601 SD video
+pub const SD_WIDTH: usize = 720;
+
+/// RGB' (0.0-1.0) of the 75% color bars, left to right
+const BARS_75: [[f64; 3]; 7] = [
+    [0.75, 0.75, 0.75], // White (75% gray)
+    [0.75, 0.75, 0.0],  // Yellow
+    [0.0, 0.75, 0.75],  // Cyan
+    [0.0, 0.75, 0.0],   // Green
+    [0.75, 0.0, 0.75],  // Magenta
+    [0.75, 0.0, 0.0],   // Red
+    [0.0, 0.0, 0.75],   // Blue
+];
+
+/// File format for an exported frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameFileFormat {
+    /// Binary PPM (P6), 8-bit RGB preview
+    Ppm,
+    /// PNG, 8-bit RGB preview
+    Png,
+    /// 10-bit 4:2:2 Cb Y Cr Y, one little-endian 16-bit word per sample
+    Uyvy10,
+    /// 10-bit 4:2:2 packed v210 (6 pixels in 16 bytes, 128-byte aligned lines)
+    V210,
+}
+
+impl FrameFileFormat {
+    pub fn as_str(&self) -> &str {
+        match self {
+            FrameFileFormat::Ppm => "ppm",
+            FrameFileFormat::Png => "png",
+            FrameFileFormat::Uyvy10 => "uyvy",
+            FrameFileFormat::V210 => "v210",
+        }
+    }
+
+    /// Format implied by a file extension (.ppm, .png, .uyvy, .yuv, .v210)
+    pub fn from_path(path: impl AsRef<Path>) -> Option<FrameFileFormat> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "yuv" => Some(FrameFileFormat::Uyvy10),
+            other => other.parse().ok(),
+        }
+    }
+}
+
+impl fmt::Display for FrameFileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FrameFileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ppm" => Ok(FrameFileFormat::Ppm),
+            "png" => Ok(FrameFileFormat::Png),
+            "uyvy" => Ok(FrameFileFormat::Uyvy10),
+            "v210" => Ok(FrameFileFormat::V210),
+            _ => Err(format!(
+                "unknown frame format '{}' (expected ppm, png, uyvy or v210)",
+                s
+            )),
+        }
+    }
+}
+
+/// One frame of the SD output: Color Black or full-field 75% color bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SdFrame {
+    pub format: VideoFormat,
+    pub video: SdVideo,
+}
+
+impl SdFrame {
+    pub fn new(format: VideoFormat, video: SdVideo) -> Self {
+        Self { format, video }
+    }
+
+    pub fn width(&self) -> usize {
+        SD_WIDTH
+    }
+
+    /// Active lines (486 for 525, 576 for 625)
+    pub fn height(&self) -> usize {
+        self.format.active_lines as usize
+    }
+
+    /// RGB' (0.0-1.0) of a sample; bars are vertical so only `x` matters
+    fn rgb(&self, x: usize) -> [f64; 3] {
+        match self.video {
+            SdVideo::ColorBlack => [0.0; 3],
+            SdVideo::ColorBars75 => BARS_75[x * BARS_75.len() / self.width()],
+        }
+    }
+
+    /// 10-bit BT.601 Y, Cb, Cr of a sample (before 4:2:2 subsampling)
+    pub fn ycbcr(&self, x: usize) -> [u16; 3] {
+        let [r, g, b] = self.rgb(x);
+        let y = 0.299 * r + 0.587 * g + 0.114 * b;
+        let cb = (b - y) / 1.772;
+        let cr = (r - y) / 1.402;
+        [
+            (64.0 + 876.0 * y).round() as u16,
+            (512.0 + 896.0 * cb).round() as u16,
+            (512.0 + 896.0 * cr).round() as u16,
+        ]
+    }
+
+    /// 8-bit RGB of one line, for previews
+    fn rgb_line(&self) -> Vec<u8> {
+        (0..self.width())
+            .flat_map(|x| self.rgb(x).map(|v| (v * 255.0).round() as u8))
+            .collect()
+    }
+
+    /// 4:2:2 samples of one line in Cb Y Cr Y order (chroma co-sited with
+    /// the even luma sample)
+    fn uyvy_line(&self) -> Vec<u16> {
+        (0..self.width())
+            .step_by(2)
+            .flat_map(|x| {
+                let [y0, cb, cr] = self.ycbcr(x);
+                let [y1, _, _] = self.ycbcr(x + 1);
+                [cb, y0, cr, y1]
+            })
+            .collect()
+    }
+
+    /// Binary PPM (P6) preview
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width(), self.height()).into_bytes();
+        let line = self.rgb_line();
+        for _ in 0..self.height() {
+            out.extend_from_slice(&line);
+        }
+        out
+    }
+
+    /// PNG preview (8-bit RGB, uncompressed deflate)
+    pub fn to_png(&self) -> Vec<u8> {
+        let line = self.rgb_line();
+        let mut raw = Vec::with_capacity((line.len() + 1) * self.height());
+        for _ in 0..self.height() {
+            raw.push(0); // filter type: none
+            raw.extend_from_slice(&line);
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width() as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height() as u32).to_be_bytes());
+        header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, no interlace
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &header);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    /// Raw 10-bit UYVY, each sample a little-endian 16-bit word
+    pub fn to_uyvy10(&self) -> Vec<u8> {
+        let line: Vec<u8> = self
+            .uyvy_line()
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        line.repeat(self.height())
+    }
+
+    /// Raw v210: three 10-bit samples per little-endian 32-bit word, lines
+    /// padded to a multiple of 128 bytes
+    pub fn to_v210(&self) -> Vec<u8> {
+        let samples = self.uyvy_line();
+        let stride = self.width().div_ceil(48) * 128;
+        let mut line: Vec<u8> = samples
+            .chunks(3)
+            .flat_map(|chunk| {
+                let word = chunk.iter().enumerate().fold(0u32, |word, (i, &sample)| {
+                    word | (sample as u32) << (10 * i)
+                });
+                word.to_le_bytes()
+            })
+            .collect();
+        line.resize(stride, 0);
+        line.repeat(self.height())
+    }
+
+    /// Frame encoded in the given file format
+    pub fn encode(&self, format: FrameFileFormat) -> Vec<u8> {
+        match format {
+            FrameFileFormat::Ppm => self.to_ppm(),
+            FrameFileFormat::Png => self.to_png(),
+            FrameFileFormat::Uyvy10 => self.to_uyvy10(),
+            FrameFileFormat::V210 => self.to_v210(),
+        }
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars() -> SdFrame {
+        SdFrame::new(VideoFormat::sd_625(), SdVideo::ColorBars75)
+    }
+
+    #[test]
+    fn test_bt601_bar_values() {
+        let frame = bars();
+        let bar = |i: usize| frame.ycbcr(i * SD_WIDTH / 7 + 10);
+        assert_eq!(bar(0), [721, 512, 512]);
+        assert_eq!(bar(1), [646, 176, 567]);
+        assert_eq!(bar(2), [525, 625, 176]);
+        assert_eq!(bar(3), [450, 289, 231]);
+        assert_eq!(bar(4), [335, 735, 793]);
+        assert_eq!(bar(5), [260, 399, 848]);
+        assert_eq!(bar(6), [139, 848, 457]);
+
+        let black = SdFrame::new(VideoFormat::sd_525(), SdVideo::ColorBlack);
+        assert_eq!(black.ycbcr(0), [64, 512, 512]);
+        assert_eq!(black.height(), 486);
+    }
+
+    #[test]
+    fn test_raw_sizes() {
+        let frame = bars();
+        assert_eq!(frame.to_uyvy10().len(), 720 * 576 * 2 * 2);
+        assert_eq!(frame.to_v210().len(), 1920 * 576);
+        assert_eq!(
+            frame.to_ppm().len(),
+            "P6\n720 576\n255\n".len() + 720 * 576 * 3
+        );
+    }
+
+    #[test]
+    fn test_v210_packing() {
+        let frame = SdFrame::new(VideoFormat::sd_525(), SdVideo::ColorBlack);
+        let data = frame.to_v210();
+        let word = |i: usize| u32::from_le_bytes(data[i * 4..i * 4 + 4].try_into().unwrap());
+        // Cb0 | Y0 << 10 | Cr0 << 20, then Y1 | Cb2 << 10 | Y2 << 20
+        assert_eq!(word(0), 512 | 64 << 10 | 512 << 20);
+        assert_eq!(word(1), 64 | 512 << 10 | 64 << 20);
+    }
+
+    #[test]
+    fn test_png_structure() {
+        let png = bars().to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        // CRC of an empty IEND chunk
+        assert_eq!(&png[png.len() - 4..], &[0xAE, 0x42, 0x60, 0x82]);
+    }
+
+    #[test]
+    fn test_file_format_from_path() {
+        assert_eq!(
+            FrameFileFormat::from_path("bars.PNG"),
+            Some(FrameFileFormat::Png)
+        );
+        assert_eq!(
+            FrameFileFormat::from_path("bars.yuv"),
+            Some(FrameFileFormat::Uyvy10)
+        );
+        assert_eq!(
+            FrameFileFormat::from_path("bars.v210"),
+            Some(FrameFileFormat::V210)
+        );
+        assert_eq!(FrameFileFormat::from_path("bars"), None);
+    }
+}
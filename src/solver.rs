@@ -0,0 +1,291 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::dipswitch::{DipSwitch, OutputConfig, SdVideo};
+use crate::profile::Profile;
+use crate::video_format::{ParseFormatError, ScanType, VideoFormat};
+
+/// Desired format for one output group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GroupTarget {
+    /// Any SD format (525 or 625 follows the frame rate switch)
+    Sd,
+    /// A specific format, matched by signal (PsF aliases included)
+    Format(VideoFormat),
+}
+
+impl GroupTarget {
+    /// Check whether a calculated group format satisfies this target
+    pub fn matches(&self, format: &VideoFormat) -> bool {
+        match self {
+            GroupTarget::Sd => !format.is_hd(),
+            GroupTarget::Format(target) => {
+                format.same_signal(target)
+                    || format
+                        .psf_equivalent()
+                        .is_some_and(|alias| alias.same_signal(target))
+            }
+        }
+    }
+
+    /// Read an interlaced format written by frame rate ("1080i25") as one
+    /// the profile produces ("1080i50")
+    ///
+    /// Interlaced names give the field rate, but the frame rate is a common
+    /// shorthand. As in the format search, the field-rate reading is kept
+    /// when the profile produces it and the frame-rate reading is tried next.
+    pub fn resolve(self, profile: &Profile) -> Self {
+        let GroupTarget::Format(format) = self else {
+            return self;
+        };
+        if format.scan != ScanType::Interlaced || !format.is_hd() {
+            return self;
+        }
+        let produced = |target: &GroupTarget| {
+            profile
+                .table_entries()
+                .any(|(_, entry)| target.matches(&entry))
+        };
+        let Some(field_rate) = format.frame_rate.times(2) else {
+            return self;
+        };
+        let by_frame_rate = GroupTarget::Format(VideoFormat::hd(
+            format.active_lines,
+            ScanType::Interlaced,
+            field_rate,
+        ));
+        if !produced(&self) && produced(&by_frame_rate) {
+            by_frame_rate
+        } else {
+            self
+        }
+    }
+}
+
+impl fmt::Display for GroupTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupTarget::Sd => write!(f, "SD"),
+            GroupTarget::Format(format) => write!(f, "{}", format),
+        }
+    }
+}
+
+impl FromStr for GroupTarget {
+    type Err = ParseFormatError;
+
+    /// Parse "sd" for any SD format, otherwise a format name such as "1080p25"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("sd") {
+            Ok(GroupTarget::Sd)
+        } else {
+            s.parse().map(GroupTarget::Format)
+        }
+    }
+}
+
+/// Desired output configuration to solve switch settings for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigTarget {
+    pub group1: GroupTarget,
+    pub group2: GroupTarget,
+    /// Required SD output (and therefore audio); `None` accepts either
+    pub sd_video: Option<SdVideo>,
+}
+
+impl ConfigTarget {
+    /// Resolve both group targets against a profile (see `GroupTarget::resolve`)
+    pub fn resolve(&self, profile: &Profile) -> Self {
+        Self {
+            group1: self.group1.resolve(profile),
+            group2: self.group2.resolve(profile),
+            sd_video: self.sd_video,
+        }
+    }
+
+    /// Check whether a calculated configuration satisfies this target
+    pub fn matches(&self, config: &OutputConfig) -> bool {
+        self.group1.matches(&config.group1_format)
+            && self.group2.matches(&config.group2_format)
+            && self
+                .sd_video
+                .is_none_or(|sd_video| sd_video == config.sd_video)
+    }
+}
+
+/// One switch setting that produces the requested configuration
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub dipswitch: DipSwitch,
+    /// The preferred setting among all equivalent ones
+    pub canonical: bool,
+}
+
+/// Find every GEN10 switch setting producing `target`, best first
+///
+/// Settings are ranked by preference: fewest of S1-S3 (switches 6-8) RIGHT,
+/// then fewest switches RIGHT overall, so the first result is the setting
+/// the manual would recommend. Only the first result is marked canonical.
+pub fn solve(target: &ConfigTarget) -> Vec<Solution> {
+    solve_with(Profile::gen10(), target)
+}
+
+/// Find every switch setting of a profile's unit producing `target`, best first
+///
+/// The general form of `solve`: the "other formats" switches that rank first
+/// are the `format_table` switches after the frame rate and line rate ones.
+pub fn solve_with(profile: &Profile, target: &ConfigTarget) -> Vec<Solution> {
+    let mut matches: Vec<DipSwitch> = profile
+        .all_settings()
+        .filter(|dipswitch| target.matches(&profile.config(dipswitch)))
+        .collect();
+
+    let other_formats = profile
+        .format_table
+        .iter()
+        .skip(2)
+        .fold(0u8, |mask, &index| mask | (1 << index));
+    matches.sort_by_key(|dipswitch| {
+        let raw = dipswitch.to_raw();
+        ((raw & other_formats).count_ones(), raw.count_ones(), raw)
+    });
+
+    matches
+        .into_iter()
+        .enumerate()
+        .map(|(i, dipswitch)| Solution {
+            dipswitch,
+            canonical: i == 0,
+        })
+        .collect()
+}
+
+/// Every distinct group target the GEN10 can produce, in Table 2 order
+pub fn available_targets() -> Vec<GroupTarget> {
+    available_targets_with(Profile::gen10())
+}
+
+/// Every distinct group target a profile's unit can produce, in table order
+pub fn available_targets_with(profile: &Profile) -> Vec<GroupTarget> {
+    let mut targets = vec![GroupTarget::Sd];
+    for (_, format) in profile.table_entries() {
+        let known = targets.iter().any(|target| match target {
+            GroupTarget::Format(existing) => existing.same_signal(&format),
+            GroupTarget::Sd => false,
+        });
+        if !known {
+            targets.push(GroupTarget::Format(format));
+        }
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_format::FrameRate;
+
+    fn hd(lines: u16, scan: ScanType, rate: FrameRate) -> GroupTarget {
+        GroupTarget::Format(VideoFormat::hd(lines, scan, rate))
+    }
+
+    #[test]
+    fn test_every_solution_matches() {
+        let target = ConfigTarget {
+            group1: hd(1080, ScanType::Progressive, FrameRate::FPS_25),
+            group2: GroupTarget::Sd,
+            sd_video: Some(SdVideo::ColorBlack),
+        };
+        let solutions = solve(&target);
+        // 1080p25 appears four times in Table 2
+        assert_eq!(solutions.len(), 4);
+        for solution in &solutions {
+            assert!(target.matches(&solution.dipswitch.get_config()));
+        }
+    }
+
+    #[test]
+    fn test_duplicates_enumerated() {
+        let target = ConfigTarget {
+            group1: hd(720, ScanType::Progressive, FrameRate::FPS_25),
+            group2: hd(720, ScanType::Progressive, FrameRate::FPS_25),
+            sd_video: Some(SdVideo::ColorBlack),
+        };
+        assert_eq!(solve(&target).len(), 6);
+    }
+
+    #[test]
+    fn test_canonical_prefers_s_bits_zero() {
+        let target = ConfigTarget {
+            group1: hd(1080, ScanType::Interlaced, FrameRate::FPS_25),
+            group2: hd(1080, ScanType::Interlaced, FrameRate::FPS_25),
+            sd_video: Some(SdVideo::ColorBlack),
+        };
+        let solutions = solve(&target);
+        assert!(solutions[0].canonical);
+        assert!(solutions.iter().skip(1).all(|s| !s.canonical));
+        for index in 5..8 {
+            assert!(!solutions[0].dipswitch.get(index));
+        }
+    }
+
+    #[test]
+    fn test_psf_alias_target() {
+        let target = ConfigTarget {
+            group1: hd(1080, ScanType::SegmentedFrame, FrameRate::FPS_25),
+            group2: GroupTarget::Sd,
+            sd_video: None,
+        };
+        let solutions = solve(&target);
+        // Only S3=1 (1080i50 / 1080psf25), for both switch 3 positions
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions[0].dipswitch.get(7));
+    }
+
+    #[test]
+    fn test_unreachable_target() {
+        // Group 1 cannot be 59.94-family while group 2 is 50-family
+        let target = ConfigTarget {
+            group1: hd(720, ScanType::Progressive, FrameRate::FPS_59_94),
+            group2: hd(720, ScanType::Progressive, FrameRate::FPS_50),
+            sd_video: None,
+        };
+        assert!(solve(&target).is_empty());
+    }
+
+    #[test]
+    fn test_parse_group_target() {
+        assert_eq!("SD".parse(), Ok(GroupTarget::Sd));
+        assert_eq!(
+            "720p50".parse(),
+            Ok(hd(720, ScanType::Progressive, FrameRate::FPS_50))
+        );
+        assert!("nope".parse::<GroupTarget>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_interlaced_frame_rate() {
+        let profile = Profile::gen10();
+        let field_rate = hd(1080, ScanType::Interlaced, FrameRate::FPS_25);
+        // "1080i25" parses as 25 fields (12.5 frames) per second
+        let frame_rate: GroupTarget = "1080i25".parse().unwrap();
+        assert_eq!(frame_rate.resolve(profile), field_rate);
+        assert_eq!(field_rate.resolve(profile), field_rate);
+
+        // Only interlaced targets are reinterpreted
+        let progressive = hd(1080, ScanType::Progressive, FrameRate::new(25, 2));
+        assert_eq!(progressive.resolve(profile), progressive);
+        assert_eq!(GroupTarget::Sd.resolve(profile), GroupTarget::Sd);
+    }
+
+    #[test]
+    fn test_available_targets_unique() {
+        let targets = available_targets();
+        assert_eq!(targets[0], GroupTarget::Sd);
+        for (i, a) in targets.iter().enumerate() {
+            for b in &targets[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! Colour themes for the interactive simulator.
+//!
+//! Built-in themes are `default`, `high-contrast`, `colorblind` (the
+//! Okabe-Ito palette, safe for red/green colour blindness) and `monochrome`.
+//! All but `default` also mark state with text, so it never depends on
+//! colour alone. More themes can be defined in `$XDG_CONFIG_HOME/gen10ds/themes.toml`:
+//!
+//! ```toml
+//! theme = "night"            # used when --theme is not given
+//!
+//! [themes.night]
+//! base = "colorblind"        # built-in theme to start from
+//! hd = "lightblue"
+//! sd = "#E69F00"
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use serde::Deserialize;
+
+use crate::config::{self, ConfigError};
+use crate::video_format::VideoFormat;
+
+/// File name of the theme config inside the config directory
+const THEME_FILE: &str = "gen10ds/themes.toml";
+
+/// Names of the built-in themes
+pub const BUILTIN_THEMES: [&str; 4] = ["default", "high-contrast", "colorblind", "monochrome"];
+
+/// Colours of the interface by role, and whether state is also shown as text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    /// Use colours at all; without them roles are told apart by modifiers
+    pub color: bool,
+    /// Mark state with text too: the active position label in brackets and
+    /// differing switches with "≠"
+    pub markers: bool,
+    /// Block titles
+    pub title: Color,
+    pub text: Color,
+    /// Field labels and secondary text
+    pub label: Color,
+    /// Hints, axes and inactive labels
+    pub muted: Color,
+    /// Keys in help bars
+    pub key: Color,
+    /// Focus, cursors and selection background
+    pub accent: Color,
+    /// Text on the accent or differs background
+    pub highlight: Color,
+    pub hd: Color,
+    pub sd: Color,
+    /// Success and active state (locked, same, active position label)
+    pub good: Color,
+    /// Errors, warnings and differences
+    pub bad: Color,
+    /// Cautions that are not errors
+    pub warn: Color,
+    /// Switch knob colour at LEFT and RIGHT; RGB colours are blended in between
+    pub switch_off: Color,
+    pub switch_on: Color,
+    /// Colours telling groups of duplicate Table 2 formats apart
+    pub groups: [Color; 6],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            name: "default".to_string(),
+            color: true,
+            markers: false,
+            title: Color::Cyan,
+            text: Color::White,
+            label: Color::Gray,
+            muted: Color::DarkGray,
+            key: Color::Yellow,
+            accent: Color::Cyan,
+            highlight: Color::Black,
+            hd: Color::Green,
+            sd: Color::Yellow,
+            good: Color::Green,
+            bad: Color::LightRed,
+            warn: Color::Yellow,
+            switch_off: Color::Rgb(128, 128, 128),
+            switch_on: Color::Rgb(0, 255, 0),
+            groups: [
+                Color::Magenta,
+                Color::LightBlue,
+                Color::LightRed,
+                Color::LightYellow,
+                Color::LightCyan,
+                Color::LightMagenta,
+            ],
+        }
+    }
+}
+
+impl Theme {
+    /// A built-in theme by name
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let default = Theme::default();
+        let theme = match name {
+            "default" => default,
+            "high-contrast" => Theme {
+                name: name.to_string(),
+                markers: true,
+                title: Color::LightCyan,
+                label: Color::White,
+                muted: Color::Gray,
+                key: Color::LightYellow,
+                accent: Color::LightCyan,
+                hd: Color::LightGreen,
+                sd: Color::LightYellow,
+                good: Color::LightGreen,
+                warn: Color::LightYellow,
+                switch_off: Color::Rgb(90, 90, 90),
+                switch_on: Color::Rgb(255, 255, 255),
+                ..default
+            },
+            // Okabe-Ito: blue/orange instead of green/red
+            "colorblind" => Theme {
+                name: name.to_string(),
+                markers: true,
+                title: Color::Rgb(86, 180, 233),
+                accent: Color::Rgb(86, 180, 233),
+                key: Color::Rgb(240, 228, 66),
+                hd: Color::Rgb(86, 180, 233),
+                sd: Color::Rgb(230, 159, 0),
+                good: Color::Rgb(0, 114, 178),
+                bad: Color::Rgb(213, 94, 0),
+                warn: Color::Rgb(230, 159, 0),
+                switch_off: Color::Rgb(128, 128, 128),
+                switch_on: Color::Rgb(0, 114, 178),
+                groups: [
+                    Color::Rgb(230, 159, 0),
+                    Color::Rgb(86, 180, 233),
+                    Color::Rgb(0, 158, 115),
+                    Color::Rgb(240, 228, 66),
+                    Color::Rgb(213, 94, 0),
+                    Color::Rgb(204, 121, 167),
+                ],
+                ..default
+            },
+            "monochrome" => Theme {
+                name: name.to_string(),
+                color: false,
+                markers: true,
+                ..default
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Pick the theme to use: `requested` (from the command line) first, then
+    /// monochrome when `NO_COLOR` is set, then the config's choice
+    pub fn select(
+        requested: Option<&str>,
+        config: &ThemeConfig,
+        no_color: bool,
+    ) -> Result<Theme, ConfigError> {
+        match (requested, no_color) {
+            (Some(name), _) => config.theme(name),
+            (None, true) => config.theme("monochrome"),
+            (None, false) => config.theme(config.selected.as_deref().unwrap_or("default")),
+        }
+    }
+
+    /// Whether the `NO_COLOR` convention asks for no colour
+    pub fn no_color_requested() -> bool {
+        std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+    }
+
+    fn fg(&self, color: Color) -> Style {
+        if self.color {
+            Style::default().fg(color)
+        } else {
+            Style::default()
+        }
+    }
+
+    pub fn title(&self) -> Style {
+        self.fg(self.title).bold()
+    }
+
+    pub fn text(&self) -> Style {
+        self.fg(self.text)
+    }
+
+    pub fn label(&self) -> Style {
+        self.fg(self.label)
+    }
+
+    pub fn muted(&self) -> Style {
+        if self.color {
+            self.fg(self.muted)
+        } else {
+            Style::default().add_modifier(Modifier::DIM)
+        }
+    }
+
+    pub fn key(&self) -> Style {
+        self.fg(self.key).bold()
+    }
+
+    /// Focused or current item in text
+    pub fn accent(&self) -> Style {
+        if self.color {
+            self.fg(self.accent)
+        } else {
+            Style::default().bold()
+        }
+    }
+
+    /// Selected list row or focused switch number
+    pub fn selected(&self) -> Style {
+        if self.color {
+            Style::default().fg(self.highlight).bg(self.accent)
+        } else {
+            Style::default().reversed()
+        }
+    }
+
+    /// Switch numbers that differ between two banks
+    pub fn differs(&self) -> Style {
+        if self.color {
+            Style::default().fg(self.highlight).bg(self.bad).bold()
+        } else {
+            Style::default().reversed().underlined().bold()
+        }
+    }
+
+    pub fn good(&self) -> Style {
+        self.fg(self.good)
+    }
+
+    pub fn bad(&self) -> Style {
+        if self.color {
+            self.fg(self.bad)
+        } else {
+            Style::default().bold()
+        }
+    }
+
+    pub fn warn(&self) -> Style {
+        if self.color {
+            self.fg(self.warn)
+        } else {
+            Style::default().bold()
+        }
+    }
+
+    /// Style of a format: HD or SD
+    pub fn format(&self, format: &VideoFormat) -> Style {
+        self.fg(self.format_color(format))
+    }
+
+    /// Canvas colour of a format, e.g. its scope trace
+    pub fn format_color(&self, format: &VideoFormat) -> Color {
+        match (self.color, format.is_hd()) {
+            (false, _) => Color::Reset,
+            (true, true) => self.hd,
+            (true, false) => self.sd,
+        }
+    }
+
+    /// Colour of duplicate format group `group`
+    pub fn group(&self, group: usize) -> Style {
+        self.fg(self.groups[group % self.groups.len()])
+    }
+
+    /// Canvas colour of grid lines
+    pub fn grid(&self) -> Color {
+        if self.color { self.muted } else { Color::Reset }
+    }
+
+    /// Knob colour of a switch at `position` (0.0 = LEFT, 1.0 = RIGHT)
+    pub fn switch_color(&self, position: f64) -> Color {
+        if !self.color {
+            return Color::Reset;
+        }
+        match (self.switch_off, self.switch_on) {
+            (Color::Rgb(r0, g0, b0), Color::Rgb(r1, g1, b1)) => {
+                let blend =
+                    |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * position) as u8;
+                Color::Rgb(blend(r0, r1), blend(g0, g1), blend(b0, b1))
+            }
+            (off, on) => {
+                if position < 0.5 {
+                    off
+                } else {
+                    on
+                }
+            }
+        }
+    }
+}
+
+/// Theme config file: the selected theme and custom themes by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThemeConfig {
+    /// Theme used when none is requested
+    pub selected: Option<String>,
+    pub themes: BTreeMap<String, Theme>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawThemeConfig {
+    theme: Option<String>,
+    #[serde(default)]
+    themes: BTreeMap<String, RawTheme>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct RawTheme {
+    base: Option<String>,
+    color: Option<bool>,
+    markers: Option<bool>,
+    title: Option<String>,
+    text: Option<String>,
+    label: Option<String>,
+    muted: Option<String>,
+    key: Option<String>,
+    accent: Option<String>,
+    highlight: Option<String>,
+    hd: Option<String>,
+    sd: Option<String>,
+    good: Option<String>,
+    bad: Option<String>,
+    warn: Option<String>,
+    switch_off: Option<String>,
+    switch_on: Option<String>,
+}
+
+impl ThemeConfig {
+    /// Location of the user's theme config, if a config directory is known
+    pub fn default_path() -> Option<PathBuf> {
+        config::user_config_file(THEME_FILE)
+    }
+
+    /// Read the theme config at `path`; a missing file has no custom themes
+    pub fn load(path: impl AsRef<Path>) -> Result<ThemeConfig, ConfigError> {
+        match config::read_optional(path)? {
+            Some(source) => ThemeConfig::from_toml_str(&source),
+            None => Ok(ThemeConfig::default()),
+        }
+    }
+
+    pub fn from_toml_str(source: &str) -> Result<ThemeConfig, ConfigError> {
+        let raw: RawThemeConfig =
+            toml::from_str(source).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        let mut themes = BTreeMap::new();
+        for (name, raw_theme) in raw.themes {
+            let theme = raw_theme.resolve(&name)?;
+            themes.insert(name, theme);
+        }
+        let config = ThemeConfig {
+            selected: raw.theme,
+            themes,
+        };
+        if let Some(selected) = &config.selected {
+            config.theme(selected)?;
+        }
+        Ok(config)
+    }
+
+    /// A custom theme or a built-in one by name
+    pub fn theme(&self, name: &str) -> Result<Theme, ConfigError> {
+        self.themes
+            .get(name)
+            .cloned()
+            .or_else(|| Theme::builtin(name))
+            .ok_or_else(|| {
+                ConfigError::Invalid(format!(
+                    "no theme named '{}' (available: {})",
+                    name,
+                    self.names().join(", ")
+                ))
+            })
+    }
+
+    /// Built-in theme names followed by the custom ones
+    pub fn names(&self) -> Vec<String> {
+        let builtin = BUILTIN_THEMES.iter().map(|name| name.to_string());
+        builtin
+            .chain(
+                self.themes
+                    .keys()
+                    .filter(|name| Theme::builtin(name).is_none())
+                    .cloned(),
+            )
+            .collect()
+    }
+}
+
+impl RawTheme {
+    fn resolve(self, name: &str) -> Result<Theme, ConfigError> {
+        let base = self.base.as_deref().unwrap_or("default");
+        let mut theme = Theme::builtin(base).ok_or_else(|| {
+            ConfigError::Invalid(format!(
+                "theme '{}': no built-in theme '{}' to start from",
+                name, base
+            ))
+        })?;
+        theme.name = name.to_string();
+        theme.color = self.color.unwrap_or(theme.color);
+        theme.markers = self.markers.unwrap_or(theme.markers);
+        let colors = [
+            (self.title, &mut theme.title),
+            (self.text, &mut theme.text),
+            (self.label, &mut theme.label),
+            (self.muted, &mut theme.muted),
+            (self.key, &mut theme.key),
+            (self.accent, &mut theme.accent),
+            (self.highlight, &mut theme.highlight),
+            (self.hd, &mut theme.hd),
+            (self.sd, &mut theme.sd),
+            (self.good, &mut theme.good),
+            (self.bad, &mut theme.bad),
+            (self.warn, &mut theme.warn),
+            (self.switch_off, &mut theme.switch_off),
+            (self.switch_on, &mut theme.switch_on),
+        ];
+        for (value, slot) in colors {
+            if let Some(value) = value {
+                *slot = value.parse().map_err(|_| {
+                    ConfigError::Invalid(format!("theme '{}': unknown colour '{}'", name, value))
+                })?;
+            }
+        }
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        for name in BUILTIN_THEMES {
+            assert_eq!(Theme::builtin(name).unwrap().name, name);
+        }
+        assert!(Theme::builtin("neon").is_none());
+        // The colour-blind and colourless themes mark state with text too
+        assert!(Theme::builtin("colorblind").unwrap().markers);
+        let mono = Theme::builtin("monochrome").unwrap();
+        assert!(mono.markers);
+        assert_eq!(mono.text(), Style::default());
+        assert_eq!(mono.switch_color(1.0), Color::Reset);
+    }
+
+    #[test]
+    fn test_switch_gradient() {
+        let theme = Theme::default();
+        assert_eq!(theme.switch_color(0.0), Color::Rgb(128, 128, 128));
+        assert_eq!(theme.switch_color(1.0), Color::Rgb(0, 255, 0));
+        assert_eq!(theme.switch_color(0.5), Color::Rgb(64, 191, 64));
+        // Named colours switch over halfway
+        let named = Theme {
+            switch_off: Color::Gray,
+            switch_on: Color::Green,
+            ..Theme::default()
+        };
+        assert_eq!(named.switch_color(0.4), Color::Gray);
+        assert_eq!(named.switch_color(0.6), Color::Green);
+    }
+
+    #[test]
+    fn test_custom_themes() {
+        let config = ThemeConfig::from_toml_str(
+            r##"
+theme = "night"
+
+[themes.night]
+base = "colorblind"
+hd = "lightblue"
+sd = "#E69F00"
+markers = false
+"##,
+        )
+        .unwrap();
+        let night = Theme::select(None, &config, false).unwrap();
+        assert_eq!(night.name, "night");
+        assert_eq!(night.hd, Color::LightBlue);
+        assert_eq!(night.sd, Color::Rgb(0xE6, 0x9F, 0x00));
+        assert_eq!(night.good, Theme::builtin("colorblind").unwrap().good);
+        assert!(!night.markers);
+        assert_eq!(config.names().last().unwrap(), "night");
+
+        // The command line wins over NO_COLOR, which wins over the config
+        assert_eq!(
+            Theme::select(Some("high-contrast"), &config, true)
+                .unwrap()
+                .name,
+            "high-contrast"
+        );
+        assert_eq!(
+            Theme::select(None, &config, true).unwrap().name,
+            "monochrome"
+        );
+        assert!(Theme::select(Some("neon"), &config, false).is_err());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        for source in [
+            "theme = \"neon\"",
+            "[themes.x]\nhd = \"not-a-colour\"",
+            "[themes.x]\nbase = \"neon\"",
+        ] {
+            assert!(
+                matches!(
+                    ThemeConfig::from_toml_str(source),
+                    Err(ConfigError::Invalid(_))
+                ),
+                "{}",
+                source
+            );
+        }
+        assert!(matches!(
+            ThemeConfig::from_toml_str("[themes.x]\nsparkle = true"),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt;
+
+use crate::video_format::{ScanType, Standard, VideoFormat};
+
+/// Exact non-negative rational number, reduced to lowest terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: u64,
+    den: u64,
+}
+
+impl Rational {
+    pub const fn new(num: u64, den: u64) -> Self {
+        assert!(den != 0, "denominator must be non-zero");
+        let divisor = gcd(num, den);
+        Self {
+            num: num / divisor,
+            den: den / divisor,
+        }
+    }
+
+    pub fn numerator(&self) -> u64 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> u64 {
+        self.den
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl fmt::Display for Rational {
+    /// "1001/60000", or just the numerator for whole numbers
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+pub(crate) const fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    if a == 0 { 1 } else { a }
+}
+
+/// Standard defining the raster and timing of a format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimingStandard {
+    /// SMPTE ST 125: 525-line 4:2:2 component digital
+    St125,
+    /// ITU-R BT.656: 625-line 4:2:2 component digital (no SMPTE equivalent)
+    Bt656,
+    /// SMPTE ST 274: 1920x1080 image structure
+    St274,
+    /// SMPTE ST 296: 1280x720 image structure
+    St296,
+}
+
+impl TimingStandard {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TimingStandard::St125 => "SMPTE ST 125",
+            TimingStandard::Bt656 => "ITU-R BT.656",
+            TimingStandard::St274 => "SMPTE ST 274",
+            TimingStandard::St296 => "SMPTE ST 296",
+        }
+    }
+}
+
+/// Reference sync waveform used for a format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncType {
+    /// Bi-level sync (blackburst) for SD
+    BiLevel,
+    /// Tri-level sync for HD
+    TriLevel,
+}
+
+impl SyncType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SyncType::BiLevel => "Bi-level (blackburst)",
+            SyncType::TriLevel => "Tri-level",
+        }
+    }
+}
+
+/// Engineering details of a video format's signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalTiming {
+    pub standard: TimingStandard,
+    pub total_lines: u16,
+    pub active_lines: u16,
+    /// Luma samples per line including horizontal blanking
+    pub samples_per_line: u32,
+    /// Luma samples in the active part of a line
+    pub active_samples: u16,
+    /// Luma sample clock in Hz
+    pub sample_clock: Rational,
+    /// Duration of one frame in seconds
+    pub frame_period: Rational,
+    /// Duration of one field (or PsF segment) in seconds, if the frame is split
+    pub field_period: Option<Rational>,
+    pub sync: SyncType,
+}
+
+impl SignalTiming {
+    /// Sample clock as it is usually written: "74.25 MHz", "74.25/1.001 MHz"
+    pub fn sample_clock_name(&self) -> String {
+        let clock = self.sample_clock;
+        let nominal = if clock.den == 1 {
+            Some((clock.num, ""))
+        } else if 1001 % clock.den == 0 {
+            Some((clock.num * (1001 / clock.den) / 1000, "/1.001"))
+        } else {
+            None
+        };
+        match nominal {
+            Some((hz, divisor)) => format!("{}{} MHz", hz as f64 / 1e6, divisor),
+            None => format!("{:.6} MHz", clock.as_f64() / 1e6),
+        }
+    }
+}
+
+impl VideoFormat {
+    /// Raster, sample clock, periods and sync type of this format's signal
+    ///
+    /// The sample clock is derived exactly from total lines, samples per line
+    /// and frame rate, so x/1.001 formats get the 74.25/1.001 MHz clock.
+    pub fn timing(&self) -> SignalTiming {
+        let rate = self.frame_rate;
+        let total_lines = self.total_lines();
+        // Nominal (integer) frame rate: 24 for 23.98, 30 for 29.97
+        let nominal_rate = rate.numerator().div_ceil(rate.denominator()) as u64;
+
+        let (standard, samples_per_line, active_samples, sync) = match self.standard {
+            Standard::Sd if total_lines == 525 => {
+                (TimingStandard::St125, 858, 720, SyncType::BiLevel)
+            }
+            Standard::Sd => (TimingStandard::Bt656, 864, 720, SyncType::BiLevel),
+            Standard::Hd => {
+                let (standard, active_samples) = if self.active_lines == 720 {
+                    (TimingStandard::St296, 1280)
+                } else {
+                    (TimingStandard::St274, 1920)
+                };
+                // 1080-line frame rates above 30 need the 148.5 MHz clock
+                let base_clock = if standard == TimingStandard::St274 && nominal_rate > 30 {
+                    148_500_000
+                } else {
+                    74_250_000
+                };
+                let samples = base_clock / (total_lines as u64 * nominal_rate);
+                (standard, samples as u32, active_samples, SyncType::TriLevel)
+            }
+        };
+
+        let frame_period = Rational::new(rate.denominator() as u64, rate.numerator() as u64);
+        let field_period = match self.scan {
+            ScanType::Interlaced | ScanType::SegmentedFrame => Some(Rational::new(
+                rate.denominator() as u64,
+                2 * rate.numerator() as u64,
+            )),
+            ScanType::Progressive => None,
+        };
+
+        SignalTiming {
+            standard,
+            total_lines,
+            active_lines: self.active_lines,
+            samples_per_line,
+            active_samples,
+            sample_clock: Rational::new(
+                total_lines as u64 * samples_per_line as u64 * rate.numerator() as u64,
+                rate.denominator() as u64,
+            ),
+            frame_period,
+            field_period,
+            sync,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_format::FrameRate;
+
+    #[test]
+    fn test_sd_timing() {
+        let ntsc = VideoFormat::sd_525().timing();
+        assert_eq!(ntsc.standard, TimingStandard::St125);
+        assert_eq!((ntsc.total_lines, ntsc.samples_per_line), (525, 858));
+        assert_eq!(ntsc.sample_clock, Rational::new(13_500_000, 1));
+        assert_eq!(ntsc.field_period, Some(Rational::new(1001, 60000)));
+        assert_eq!(ntsc.sync, SyncType::BiLevel);
+
+        let pal = VideoFormat::sd_625().timing();
+        assert_eq!((pal.total_lines, pal.samples_per_line), (625, 864));
+        assert_eq!(pal.sample_clock_name(), "13.5 MHz");
+        assert_eq!(pal.frame_period, Rational::new(1, 25));
+    }
+
+    #[test]
+    fn test_1080_samples_per_line() {
+        for (rate, samples) in [
+            (FrameRate::FPS_23_98, 2750),
+            (FrameRate::FPS_24, 2750),
+            (FrameRate::FPS_25, 2640),
+            (FrameRate::FPS_29_97, 2200),
+            (FrameRate::FPS_30, 2200),
+        ] {
+            let timing = VideoFormat::hd(1080, ScanType::Progressive, rate).timing();
+            assert_eq!(timing.standard, TimingStandard::St274);
+            assert_eq!(timing.samples_per_line, samples, "{}", rate);
+            assert_eq!(timing.total_lines, 1125);
+        }
+    }
+
+    #[test]
+    fn test_720_samples_per_line() {
+        for (rate, samples) in [
+            (FrameRate::FPS_23_98, 4125),
+            (FrameRate::FPS_25, 3960),
+            (FrameRate::FPS_30, 3300),
+            (FrameRate::FPS_50, 1980),
+            (FrameRate::FPS_59_94, 1650),
+        ] {
+            let timing = VideoFormat::hd(720, ScanType::Progressive, rate).timing();
+            assert_eq!(timing.standard, TimingStandard::St296);
+            assert_eq!(timing.samples_per_line, samples, "{}", rate);
+            assert_eq!(timing.field_period, None);
+        }
+    }
+
+    #[test]
+    fn test_low_rate_samples_per_line() {
+        // Outside the SMPTE rates the line is longer than a u16 can count
+        let timing = VideoFormat::hd(1080, ScanType::Progressive, FrameRate::new(1, 1)).timing();
+        assert_eq!(timing.samples_per_line, 66_000);
+        assert_eq!(timing.sample_clock, Rational::new(74_250_000, 1));
+    }
+
+    #[test]
+    fn test_hd_sample_clock() {
+        let i5994 = VideoFormat::hd(1080, ScanType::Interlaced, FrameRate::FPS_29_97).timing();
+        assert_eq!(i5994.sample_clock, Rational::new(74_250_000_000, 1001));
+        assert_eq!(i5994.sample_clock_name(), "74.25/1.001 MHz");
+        assert_eq!(i5994.frame_period.to_string(), "1001/30000");
+        assert_eq!(i5994.field_period.unwrap().to_string(), "1001/60000");
+        assert_eq!(i5994.sync, SyncType::TriLevel);
+
+        let p50 = VideoFormat::hd(720, ScanType::Progressive, FrameRate::FPS_50).timing();
+        assert_eq!(p50.sample_clock_name(), "74.25 MHz");
+
+        let p1080_50 = VideoFormat::hd(1080, ScanType::Progressive, FrameRate::FPS_50).timing();
+        assert_eq!(p1080_50.sample_clock_name(), "148.5 MHz");
+    }
+
+    #[test]
+    fn test_psf_has_segments() {
+        let psf = VideoFormat::hd(1080, ScanType::SegmentedFrame, FrameRate::FPS_24).timing();
+        assert_eq!(psf.field_period, Some(Rational::new(1, 48)));
+        assert_eq!(psf.sample_clock, Rational::new(74_250_000, 1));
+    }
+}
@@ -1,69 +1,452 @@
+use crossterm::event::KeyCode;
 use ratatui::{
-    layout::{Constraint, Layout, Rect},
-    style::{Color, Style, Stylize},
+    Frame,
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Style, Stylize},
     symbols::Marker,
     text::{Line, Span},
     widgets::{
-        canvas::{Canvas, Circle},
-        Block, Borders, Paragraph,
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap,
+        canvas::{Canvas, Circle, Line as CanvasLine},
     },
-    Frame,
 };
 
-use crate::app::App;
+use crate::app::{App, Mode, SolverFocus, SwitchAnimation};
+use crate::audio::{AudioReference, GroupAudio};
+use crate::dipswitch::DipSwitch;
+use crate::profile::Profile;
+use crate::solver::GroupTarget;
+use crate::theme::Theme;
+use crate::video_format::VideoFormat;
+use crate::waveform::{Waveform, WaveformSpan};
 
-/// Main render function
-pub fn render(frame: &mut Frame, app: &App) {
-    let main_layout = Layout::vertical([
-        Constraint::Min(35),   // DIP switches section
-        Constraint::Length(8), // Configuration section
-        Constraint::Length(3), // Keybindings
-    ]);
+/// Width of a format cell in the Table 2 browser
+const TABLE_CELL_WIDTH: u16 = 28;
+
+/// Amplitude range of the oscilloscope panel in mV
+const SCOPE_RANGE_MV: f64 = 350.0;
+
+/// Rows of a switch drawn as a full-size row
+const SWITCH_ROW_HEIGHT: u16 = 4;
+
+/// Columns of a switch drawn as a vertical column
+const SWITCH_COLUMN_WIDTH: u16 = 9;
+
+/// Rows of a bank of vertical columns, borders included
+const COLUMN_BANK_HEIGHT: u16 = 12;
+
+/// Rows of the configuration and timing panels
+const DETAILS_HEIGHT: u16 = 10;
+
+/// Rows of the group cadence panel
+const CADENCE_HEIGHT: u16 = 4;
+
+/// Rows of the keybindings bar with two lines of hints
+const KEYS_HEIGHT: u16 = 4;
+
+/// Width of the timing panel, and of the side column of the wide layout
+const SIDE_WIDTH: u16 = 66;
+
+/// Width of the configuration panel beside the timing panel
+const CONFIG_WIDTH: u16 = 56;
+
+/// Width of the oscilloscope panel beside the switches
+const SCOPE_WIDTH: u16 = 60;
+
+/// Rows the oscilloscope panel needs for its trace and notes
+const SCOPE_HEIGHT: u16 = 13;
 
-    let [switches_area, config_area, keys_area] = main_layout.areas(frame.area());
+/// Narrowest terminal for the wide layout
+const WIDE_MIN_WIDTH: u16 = 140;
 
-    render_switches(frame, switches_area, app);
+/// Arrangement of the main screen, chosen by terminal size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMode {
+    /// Full-size switches with configuration, timing and cadence beside them
+    Wide,
+    /// Full-size switches above configuration, timing and cadence
+    Standard,
+    /// Switches as vertical columns like the physical DIP block, RIGHT up
+    Horizontal,
+    /// One row per switch
+    Compact,
+}
+
+/// How the switches of a bank are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BankStyle {
+    /// A labelled row with a sliding knob per switch
+    Rows,
+    /// A column with a knob moving up (RIGHT) and down (LEFT) per switch
+    Columns,
+    /// A single line per switch
+    Compact,
+}
+
+impl BankStyle {
+    /// Smallest useful size of a bank of `switches`, borders included
+    fn min_size(self, switches: u16) -> (u16, u16) {
+        match self {
+            BankStyle::Rows => (60, switches * SWITCH_ROW_HEIGHT + 2),
+            BankStyle::Columns => (switches * SWITCH_COLUMN_WIDTH + 2, COLUMN_BANK_HEIGHT),
+            BankStyle::Compact => (44, switches + 2),
+        }
+    }
+}
+
+impl LayoutMode {
+    /// Layout for a terminal of `width` x `height` showing `switches` switches
+    pub fn for_size(width: u16, height: u16, switches: usize) -> LayoutMode {
+        let (_, rows_height) = BankStyle::Rows.min_size(switches as u16);
+        if width >= WIDE_MIN_WIDTH && height >= rows_height + KEYS_HEIGHT {
+            LayoutMode::Wide
+        } else {
+            LayoutMode::stacked(width, height, switches)
+        }
+    }
+
+    /// Layout for the view in `mode`: as `for_size`, except that the compare
+    /// view, whose table needs the full width, is never wide
+    pub fn for_mode(width: u16, height: u16, switches: usize, mode: Mode) -> LayoutMode {
+        match LayoutMode::for_size(width, height, switches) {
+            LayoutMode::Wide if mode == Mode::Compare => {
+                LayoutMode::stacked(width, height, switches)
+            }
+            layout => layout,
+        }
+    }
+
+    /// Largest layout with the details below the switches that fits
+    pub fn stacked(width: u16, height: u16, switches: usize) -> LayoutMode {
+        let below = DETAILS_HEIGHT + CADENCE_HEIGHT + KEYS_HEIGHT;
+        let (_, rows_height) = BankStyle::Rows.min_size(switches as u16);
+        let (columns_width, columns_height) = BankStyle::Columns.min_size(switches as u16);
+        if height >= rows_height + below {
+            LayoutMode::Standard
+        } else if width >= columns_width && height >= columns_height + below {
+            LayoutMode::Horizontal
+        } else {
+            LayoutMode::Compact
+        }
+    }
+
+    fn bank_style(self) -> BankStyle {
+        match self {
+            LayoutMode::Wide | LayoutMode::Standard => BankStyle::Rows,
+            LayoutMode::Horizontal => BankStyle::Columns,
+            LayoutMode::Compact => BankStyle::Compact,
+        }
+    }
+}
+
+/// Clickable element of the interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitTarget {
+    /// A switch (0-based switch index)
+    Switch(usize),
+    /// A switch of a compare bank (0 = current unit, 1 = required)
+    BankSwitch { bank: usize, index: usize },
+    /// A cell of the Table 2 browser
+    TableCell { row: usize, column: usize },
+    /// A key hint; clicking it acts like pressing the key
+    Key(KeyCode),
+}
+
+/// Screen regions of the clickable elements drawn in a frame
+#[derive(Debug, Clone, Default)]
+pub struct HitMap {
+    regions: Vec<(Rect, HitTarget)>,
+}
+
+impl HitMap {
+    fn push(&mut self, area: Rect, target: HitTarget) {
+        self.regions.push((area, target));
+    }
+
+    /// Element at a terminal cell with its region; later (upper) elements win
+    pub fn hit(&self, column: u16, row: u16) -> Option<(Rect, HitTarget)> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(area, _)| area.contains(Position::new(column, row)))
+            .copied()
+    }
+}
+
+/// Main render function; returns where the clickable elements were drawn
+pub fn render(frame: &mut Frame, app: &App) -> HitMap {
+    let mut hits = HitMap::default();
+    let area = frame.area();
+    let switches = app.profile.switch_count();
+    let layout = LayoutMode::for_mode(area.width, area.height, switches, app.mode);
+    let keys_height = keybindings_height(app, area.width);
+    let [main_area, keys_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(keys_height)]).areas(area);
+
+    if layout == LayoutMode::Wide {
+        render_wide(frame, main_area, app, &mut hits);
+    } else {
+        render_stacked(frame, main_area, layout.bank_style(), app, &mut hits);
+    }
+    render_keybindings(frame, keys_area, app, &mut hits);
+
+    if app.mode == Mode::Solver {
+        // The popup takes all clicks while it is open
+        hits = HitMap::default();
+        render_solver(frame, centered_rect(frame.area(), 90, 80), app, &mut hits);
+    }
+    if app.mode == Mode::Table {
+        hits = HitMap::default();
+        render_table(frame, app, &mut hits);
+    }
+    if app.mode == Mode::Search {
+        hits = HitMap::default();
+        render_search(frame, centered_rect(frame.area(), 60, 70), app, &mut hits);
+    }
+    if app.mode == Mode::History {
+        hits = HitMap::default();
+        render_history(frame, centered_rect(frame.area(), 80, 70), app, &mut hits);
+    }
+    if app.mode == Mode::Presets {
+        hits = HitMap::default();
+        render_presets(frame, centered_rect(frame.area(), 80, 70), app, &mut hits);
+    }
+    hits
+}
+
+/// Render the wide layout: the switches on the left, and configuration,
+/// timing, cadence and the scope (or the toggle plan) stacked on the right
+fn render_wide(frame: &mut Frame, area: Rect, app: &App, hits: &mut HitMap) {
+    let [switches_area, side_area] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(SIDE_WIDTH)]).areas(area);
+    render_switches(frame, switches_area, BankStyle::Rows, app, hits);
+    if app.mode == Mode::Plan {
+        render_plan(frame, side_area, app);
+        return;
+    }
+    // The cadence lines wrap in the narrower column; the scope goes below
+    // the timing if there is room, else in its place
+    let cadence_height = CADENCE_HEIGHT + 2;
+    let scope_below = side_area.height >= 2 * DETAILS_HEIGHT + cadence_height + SCOPE_HEIGHT;
+    let (middle, rest) = if app.scope.visible && !scope_below {
+        (Constraint::Min(0), Constraint::Length(0))
+    } else {
+        (Constraint::Length(DETAILS_HEIGHT), Constraint::Min(0))
+    };
+    let [config_area, middle_area, cadence_area, rest_area] = Layout::vertical([
+        Constraint::Length(DETAILS_HEIGHT),
+        middle,
+        Constraint::Length(cadence_height),
+        rest,
+    ])
+    .areas(side_area);
     render_config(frame, config_area, app);
-    render_keybindings(frame, keys_area);
+    render_cadence(frame, cadence_area, app);
+    match (app.scope.visible, scope_below) {
+        (false, _) => render_timing(frame, middle_area, app),
+        (true, true) => {
+            render_timing(frame, middle_area, app);
+            render_scope(frame, rest_area, app, hits);
+        }
+        (true, false) => render_scope(frame, middle_area, app, hits),
+    }
 }
 
-/// Render all 8 DIP switches
-fn render_switches(frame: &mut Frame, area: Rect, app: &App) {
+/// Render a layout with the details below the switches drawn in `style`;
+/// the scope goes beside the switches if there is room, else in place of
+/// the configuration and timing
+fn render_stacked(frame: &mut Frame, area: Rect, style: BankStyle, app: &App, hits: &mut HitMap) {
+    let switches = app.profile.switch_count() as u16;
+    let (bank_width, bank_height) = style.min_size(switches);
+    let constraints = match style {
+        BankStyle::Rows | BankStyle::Columns => [
+            Constraint::Min(bank_height),
+            Constraint::Length(DETAILS_HEIGHT),
+            Constraint::Length(CADENCE_HEIGHT),
+        ],
+        // Small terminals: what is left goes to the configuration
+        BankStyle::Compact => [
+            Constraint::Length(bank_height),
+            Constraint::Min(0),
+            Constraint::Length(0),
+        ],
+    };
+    let [switches_area, details_area, cadence_area] = Layout::vertical(constraints).areas(area);
+
+    if app.mode == Mode::Compare {
+        // Two banks of columns side by side rarely fit; list them instead
+        let style = match style {
+            BankStyle::Columns if area.width < 2 * bank_width => BankStyle::Compact,
+            style => style,
+        };
+        // The comparison covers both groups, so it takes the cadence rows too
+        render_compare(
+            frame,
+            switches_area,
+            details_area.union(cadence_area),
+            style,
+            app,
+            hits,
+        );
+        return;
+    }
+
+    let scope_beside = app.scope.visible && area.width >= bank_width + SCOPE_WIDTH;
+    if scope_beside {
+        let [bank_area, scope_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(SCOPE_WIDTH)])
+                .areas(switches_area);
+        render_switches(frame, bank_area, style, app, hits);
+        render_scope(frame, scope_area, app, hits);
+    } else {
+        render_switches(frame, switches_area, style, app, hits);
+    }
+
+    if app.mode == Mode::Plan {
+        render_plan(frame, details_area.union(cadence_area), app);
+        return;
+    }
+    if app.scope.visible && !scope_beside {
+        render_scope(frame, details_area, app, hits);
+    } else if details_area.width >= CONFIG_WIDTH + SIDE_WIDTH {
+        let [config_area, timing_area] = Layout::horizontal([
+            Constraint::Min(CONFIG_WIDTH),
+            Constraint::Length(SIDE_WIDTH),
+        ])
+        .areas(details_area);
+        render_config(frame, config_area, app);
+        render_timing(frame, timing_area, app);
+    } else {
+        render_config(frame, details_area, app);
+    }
+    if !cadence_area.is_empty() {
+        render_cadence(frame, cadence_area, app);
+    }
+}
+
+/// Rect of the given percentage size centered in `area`
+fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let [_, vertical, _] = Layout::vertical([
+        Constraint::Percentage((100 - percent_y) / 2),
+        Constraint::Percentage(percent_y),
+        Constraint::Percentage((100 - percent_y) / 2),
+    ])
+    .areas(area);
+    let [_, centered, _] = Layout::horizontal([
+        Constraint::Percentage((100 - percent_x) / 2),
+        Constraint::Percentage(percent_x),
+        Constraint::Percentage((100 - percent_x) / 2),
+    ])
+    .areas(vertical);
+    centered
+}
+
+/// Render all DIP switches of the loaded profile
+fn render_switches(frame: &mut Frame, area: Rect, style: BankStyle, app: &App, hits: &mut HitMap) {
+    let bank = BankView {
+        style,
+        dipswitch: &app.dipswitch,
+        animations: &app.animations,
+        focus: matches!(app.mode, Mode::Normal | Mode::Plan).then_some(app.focus),
+        other: None,
+        theme: &app.theme,
+    };
+    let title = format!("{} DIP Switch Simulator", app.profile.name);
+    render_bank(
+        frame,
+        area,
+        &title,
+        &app.profile,
+        &bank,
+        hits,
+        HitTarget::Switch,
+    );
+}
+
+/// A bank of switches as drawn: positions, animations and highlights
+struct BankView<'a> {
+    style: BankStyle,
+    dipswitch: &'a DipSwitch,
+    animations: &'a [SwitchAnimation; 8],
+    /// Switch with the keyboard focus, if the bank receives keys
+    focus: Option<usize>,
+    /// Setting whose differing switches are highlighted
+    other: Option<&'a DipSwitch>,
+    theme: &'a Theme,
+}
+
+/// Render a bank of switches in a titled block, recording each switch as
+/// `target(index)`
+fn render_bank(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    profile: &Profile,
+    bank: &BankView,
+    hits: &mut HitMap,
+    target: impl Fn(usize) -> HitTarget,
+) {
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("GEN10 DIP Switch Simulator")
-        .title_style(Style::default().fg(Color::Cyan).bold());
+        .title(title.to_string())
+        .title_style(bank.theme.title());
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    // Switch labels configuration
-    let switch_configs: [(Option<&str>, &str, Option<&str>); 8] = [
-        (Some("SD"), "OUT 1-4", Some("HD")),
-        (Some("SD"), "OUT 5-6", Some("HD")),
-        (Some("BLK"), "SD OUT", Some("BARS")),
-        (Some("59.94"), "FORMAT", Some("50")),
-        (Some("1080"), "HD FMT", Some("720")),
-        (None, "S1", None),
-        (None, "S2", None),
-        (None, "S3", None),
-    ];
+    let switch_count = profile.switch_count();
+    if bank.style == BankStyle::Columns {
+        // Columns share the width, left over cells go to the margins
+        let width = (inner.width / switch_count.max(1) as u16).min(SWITCH_COLUMN_WIDTH + 2);
+        let mut x_offset = inner.x + (inner.width - width * switch_count as u16) / 2;
+        for (i, spec) in profile.switches.iter().enumerate() {
+            let switch_area = Rect::new(x_offset, inner.y, width, inner.height);
+            let labels = (
+                spec.left.as_deref(),
+                spec.function.as_str(),
+                spec.right.as_deref(),
+            );
+            render_switch_column(frame, switch_area, i, bank, &labels);
+            hits.push(switch_area.intersection(inner), target(i));
+            x_offset += width;
+        }
+        return;
+    }
 
-    // Calculate spacing for 8 switches
-    let switch_height = 4;
+    // Calculate spacing between switches
+    let switch_height = if bank.style == BankStyle::Rows {
+        SWITCH_ROW_HEIGHT as usize
+    } else {
+        1
+    };
     let available_height = inner.height as usize;
-    let total_switch_height = switch_height * 8;
+    let total_switch_height = switch_height * switch_count;
     let spacing = if available_height > total_switch_height {
-        (available_height - total_switch_height) / 9
+        (available_height - total_switch_height) / (switch_count + 1)
     } else {
         0
     };
 
     let mut y_offset = inner.y + spacing as u16;
 
-    for (i, config) in switch_configs.iter().enumerate() {
-        let switch_area = Rect::new(inner.x, y_offset, inner.width, switch_height as u16);
-        render_single_switch(frame, switch_area, i, app, config);
+    for (i, spec) in profile.switches.iter().enumerate() {
+        // Rows that do not fit a short terminal are left out
+        let switch_area =
+            Rect::new(inner.x, y_offset, inner.width, switch_height as u16).intersection(inner);
+        if switch_area.is_empty() {
+            break;
+        }
+        let labels = (
+            spec.left.as_deref(),
+            spec.function.as_str(),
+            spec.right.as_deref(),
+        );
+        if bank.style == BankStyle::Rows {
+            render_single_switch(frame, switch_area, i, bank, &labels);
+        } else {
+            render_compact_switch(frame, switch_area, i, bank, &labels);
+        }
+        hits.push(switch_area, target(i));
         y_offset += switch_height as u16 + spacing as u16;
     }
 }
@@ -73,12 +456,13 @@ fn render_single_switch(
     frame: &mut Frame,
     area: Rect,
     index: usize,
-    app: &App,
+    bank: &BankView,
     labels: &(Option<&str>, &str, Option<&str>),
 ) {
     let (left_label, center_label, right_label) = labels;
-    let position = app.animations[index].position();
-    let state = app.dipswitch.get(index);
+    let theme = bank.theme;
+    let position = bank.animations[index].position();
+    let state = bank.dipswitch.get(index);
 
     // Layout: [left_label][number][canvas][right_label]
     let horizontal = Layout::horizontal([
@@ -92,10 +476,10 @@ fn render_single_switch(
 
     // Render left label
     if let Some(label) = left_label {
-        let label_color = if !state { Color::White } else { Color::DarkGray };
+        let (text, style) = position_label(theme, label, !state, theme.text());
         frame.render_widget(
-            Paragraph::new(*label)
-                .style(Style::default().fg(label_color))
+            Paragraph::new(text)
+                .style(style)
                 .right_aligned(),
             parts[0],
         );
@@ -103,15 +487,13 @@ fn render_single_switch(
 
     // Render switch number
     frame.render_widget(
-        Paragraph::new(format!("{}", index + 1))
-            .style(Style::default().fg(Color::White).bold())
-            .centered(),
+        Paragraph::new(switch_number(bank, index)).centered(),
         parts[1],
     );
 
     // Render canvas switch
     let canvas_area = parts[2];
-    let switch_canvas = create_switch_canvas(position, state);
+    let switch_canvas = create_switch_canvas(theme, position, false);
     frame.render_widget(switch_canvas, canvas_area);
 
     // Render center label below canvas
@@ -122,45 +504,451 @@ fn render_single_switch(
         1,
     );
     frame.render_widget(
-        Paragraph::new(*center_label)
-            .style(Style::default().fg(Color::Gray))
-            .centered(),
+        Paragraph::new(function_label(bank, index, center_label)).centered(),
         label_area,
     );
 
     // Render right label
     if let Some(label) = right_label {
-        let label_color = if state { Color::Green } else { Color::DarkGray };
+        let (text, style) = position_label(theme, label, state, theme.good());
         frame.render_widget(
-            Paragraph::new(*label)
-                .style(Style::default().fg(label_color))
+            Paragraph::new(text)
+                .style(style)
                 .left_aligned(),
             parts[3],
         );
     }
 }
 
+/// Render a switch as a vertical column: the RIGHT label on top, the knob
+/// moving up for RIGHT and down for LEFT, then the LEFT label and function
+fn render_switch_column(
+    frame: &mut Frame,
+    area: Rect,
+    index: usize,
+    bank: &BankView,
+    labels: &(Option<&str>, &str, Option<&str>),
+) {
+    let (left_label, center_label, right_label) = labels;
+    let theme = bank.theme;
+    let state = bank.dipswitch.get(index);
+    let [
+        number_area,
+        top_area,
+        canvas_area,
+        bottom_area,
+        function_area,
+    ] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(2),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(area);
+
+    frame.render_widget(
+        Paragraph::new(switch_number(bank, index)).centered(),
+        number_area,
+    );
+    if let Some(label) = right_label {
+        let (text, style) = position_label(theme, label, state, theme.good());
+        frame.render_widget(Paragraph::new(text).style(style).centered(), top_area);
+    }
+    let position = bank.animations[index].position();
+    frame.render_widget(create_switch_canvas(theme, position, true), canvas_area);
+    if let Some(label) = left_label {
+        let (text, style) = position_label(theme, label, !state, theme.text());
+        frame.render_widget(Paragraph::new(text).style(style).centered(), bottom_area);
+    }
+    frame.render_widget(
+        Paragraph::new(function_label(bank, index, center_label)).centered(),
+        function_area,
+    );
+}
+
+/// Render a switch on a single line: number, LEFT label, a text slider with
+/// the knob, RIGHT label and function
+fn render_compact_switch(
+    frame: &mut Frame,
+    area: Rect,
+    index: usize,
+    bank: &BankView,
+    labels: &(Option<&str>, &str, Option<&str>),
+) {
+    let (left_label, center_label, right_label) = labels;
+    let theme = bank.theme;
+    let state = bank.dipswitch.get(index);
+    let position = bank.animations[index].position();
+
+    // Knob on a track of fixed length, placed by the animation
+    const TRACK: usize = 7;
+    let knob = (position * (TRACK - 1) as f64).round() as usize;
+    let track = |count: usize| Span::styled("─".repeat(count), theme.muted());
+    let knob_style = Style::default().fg(theme.switch_color(position)).bold();
+    let knob_style = if theme.color {
+        knob_style
+    } else {
+        Style::default().bold()
+    };
+
+    let mut spans = vec![switch_number(bank, index), Span::raw(" ")];
+    let (text, style) = left_label.map_or((String::new(), theme.muted()), |label| {
+        position_label(theme, label, !state, theme.text())
+    });
+    spans.push(Span::styled(format!("{:>8} ", text), style));
+    spans.push(track(knob));
+    spans.push(Span::styled("●", knob_style));
+    spans.push(track(TRACK - 1 - knob));
+    let (text, style) = right_label.map_or((String::new(), theme.muted()), |label| {
+        position_label(theme, label, state, theme.good())
+    });
+    spans.push(Span::styled(format!(" {:<8} ", text), style));
+    spans.push(function_label(bank, index, center_label));
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Switch number, marked when the switch has the focus and highlighted when
+/// it differs from the other bank
+fn switch_number(bank: &BankView, index: usize) -> Span<'static> {
+    let theme = bank.theme;
+    let focused = bank.focus == Some(index);
+    let differs = bank
+        .other
+        .is_some_and(|other| other.get(index) != bank.dipswitch.get(index));
+    let number = if focused {
+        format!(">{}<", index + 1)
+    } else {
+        format!(" {} ", index + 1)
+    };
+    let style = match (focused, differs) {
+        (_, true) => theme.differs(),
+        (true, false) => theme.selected().bold(),
+        (false, false) => theme.text().bold(),
+    };
+    Span::styled(number, style)
+}
+
+/// Function label of a switch; markers flag a differing switch in text as
+/// well
+fn function_label<'a>(bank: &BankView, index: usize, label: &'a str) -> Span<'a> {
+    let theme = bank.theme;
+    let style = if bank.focus == Some(index) {
+        theme.accent()
+    } else {
+        theme.label()
+    };
+    let differs = bank
+        .other
+        .is_some_and(|other| other.get(index) != bank.dipswitch.get(index));
+    if differs && theme.markers {
+        Span::styled(format!("≠ {}", label), style)
+    } else {
+        Span::styled(label, style)
+    }
+}
+
+/// Text and style of a position label; with markers the active position's
+/// label is bracketed so it does not rely on colour
+fn position_label(
+    theme: &Theme,
+    label: &str,
+    active: bool,
+    active_style: Style,
+) -> (String, Style) {
+    match (active, theme.markers) {
+        (true, true) => (format!("[{}]", label), active_style.bold()),
+        (true, false) => (label.to_string(), active_style),
+        (false, _) => (label.to_string(), theme.muted()),
+    }
+}
+
+/// Render the compare view: the unit's switches and the required bank side
+/// by side with differing switches highlighted, and the resulting outputs of
+/// both compared row by row
+fn render_compare(
+    frame: &mut Frame,
+    banks_area: Rect,
+    details_area: Rect,
+    style: BankStyle,
+    app: &App,
+    hits: &mut HitMap,
+) {
+    let theme = &app.theme;
+    let [current_area, required_area] =
+        Layout::horizontal([Constraint::Ratio(1, 2); 2]).areas(banks_area);
+    let titles = ["CURRENT UNIT", "REQUIRED"];
+    let banks = [
+        (&app.dipswitch, &app.animations, &app.compare.required),
+        (
+            &app.compare.required,
+            &app.compare.animations,
+            &app.dipswitch,
+        ),
+    ];
+    for (bank, (area, (dipswitch, animations, other))) in [current_area, required_area]
+        .into_iter()
+        .zip(banks)
+        .enumerate()
+    {
+        let selected = app.compare.bank == bank;
+        let view = BankView {
+            style,
+            dipswitch,
+            animations,
+            focus: selected.then_some(app.focus),
+            other: Some(other),
+            theme: &app.theme,
+        };
+        let title = if selected {
+            format!("> {} <", titles[bank])
+        } else {
+            titles[bank].to_string()
+        };
+        render_bank(frame, area, &title, &app.profile, &view, hits, |index| {
+            HitTarget::BankSwitch { bank, index }
+        });
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("COMPARISON")
+        .title_style(theme.title());
+    let inner = block.inner(details_area);
+    frame.render_widget(block, details_area);
+    // Value columns shrink on narrow terminals to keep the status in view
+    let width = (inner.width.saturating_sub(32) / 2).clamp(12, 34) as usize;
+    let cell = |text: &str| format!("{:<width$}", fit(text, width - 1), width = width);
+
+    let current = app.profile.config(&app.dipswitch);
+    let required = app.profile.config(&app.compare.required);
+    let rows = [
+        (
+            "Group 1 (Out 1-4):",
+            current.group1_format.to_string(),
+            required.group1_format.to_string(),
+            current.group1_format.same_signal(&required.group1_format),
+        ),
+        (
+            "Group 2 (Out 5-6):",
+            current.group2_format.to_string(),
+            required.group2_format.to_string(),
+            current.group2_format.same_signal(&required.group2_format),
+        ),
+        (
+            "SD Output:",
+            format!("{} {}", current.sd_format.name(), current.sd_video.as_str()),
+            format!(
+                "{} {}",
+                required.sd_format.name(),
+                required.sd_video.as_str()
+            ),
+            current.sd_format.same_signal(&required.sd_format)
+                && current.sd_video == required.sd_video,
+        ),
+        (
+            "Audio (AES-11):",
+            current.audio.as_str().to_string(),
+            required.audio.as_str().to_string(),
+            current.audio == required.audio,
+        ),
+    ];
+
+    let mut lines = vec![Line::from(vec![
+        Span::raw(format!("  {:<20}", "")),
+        Span::styled(cell(titles[0]), theme.label().bold()),
+        Span::styled(titles[1], theme.label().bold()),
+    ])];
+    let mut outputs_match = true;
+    for (label, current, required, same) in rows {
+        outputs_match &= same;
+        let (status, style) = if same {
+            ("same", theme.good())
+        } else {
+            ("DIFFERENT", theme.bad())
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("  {:<20}", label)),
+            Span::styled(cell(&current), theme.text()),
+            Span::styled(cell(&required), theme.text()),
+            Span::styled(status, style.bold()),
+        ]));
+    }
+
+    let moves: Vec<String> = (0..app.profile.switch_count())
+        .filter(|&i| app.dipswitch.get(i) != app.compare.required.get(i))
+        .map(|i| (i + 1).to_string())
+        .collect();
+    lines.push(Line::from(vec![
+        Span::raw(format!("  {:<20}", "Switches:")),
+        Span::styled(cell(&app.profile.positions(&app.dipswitch)), theme.text()),
+        Span::styled(
+            cell(&app.profile.positions(&app.compare.required)),
+            theme.text(),
+        ),
+        Span::styled(
+            if moves.is_empty() {
+                "same".to_string()
+            } else {
+                format!("differ: {}", moves.join(", "))
+            },
+            if moves.is_empty() {
+                theme.good()
+            } else {
+                theme.bad()
+            }
+            .bold(),
+        ),
+    ]));
+    let (summary, style) = match (moves.is_empty(), outputs_match) {
+        (true, _) => ("The unit is set as required".to_string(), theme.good()),
+        (false, true) => (
+            "Same outputs from a different setting: the unit already produces what is required"
+                .to_string(),
+            theme.warn(),
+        ),
+        (false, false) => (
+            format!(
+                "Move switch{} {} on the unit to match",
+                if moves.len() == 1 { "" } else { "es" },
+                moves.join(", ")
+            ),
+            theme.bad(),
+        ),
+    };
+    lines.push(Line::default());
+    lines.push(Line::styled(format!("  {}", summary), style));
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// `text` cut to `width` characters, ending in "…" when cut
+fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+/// Render the toggle plan being played back: the checklist of moves with the
+/// ones made ticked and the next one marked, beside the two settings
+fn render_plan(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = &app.theme;
+    let view = &app.plan;
+    let plan = &view.plan;
+    let total = plan.moves.len();
+    let progress = if view.done == total {
+        "done".to_string()
+    } else {
+        format!("move {} of {}", view.done + 1, total)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "TOGGLE PLAN - {}{}",
+            progress,
+            if view.playing { " (playing)" } else { "" }
+        ))
+        .title_style(theme.title());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [steps_area, settings_area] =
+        Layout::horizontal([Constraint::Min(40), Constraint::Length(62)]).areas(inner);
+
+    let steps: Vec<ListItem> = if plan.is_empty() {
+        vec![ListItem::new(Line::styled(
+            "  Nothing to move: the switches are already set",
+            theme.good(),
+        ))]
+    } else {
+        plan.moves
+            .iter()
+            .enumerate()
+            .map(|(step, switch_move)| {
+                let (check, style) = match step.cmp(&view.done) {
+                    std::cmp::Ordering::Less => ("[x]", theme.good()),
+                    std::cmp::Ordering::Equal => ("[ ]", theme.accent().bold()),
+                    std::cmp::Ordering::Greater => ("[ ]", theme.text()),
+                };
+                let marker = if step == view.done { "> " } else { "  " };
+                ListItem::new(Line::styled(
+                    format!(
+                        "{}{} {}. {}",
+                        marker,
+                        check,
+                        step + 1,
+                        switch_move.describe(&app.profile)
+                    ),
+                    style,
+                ))
+            })
+            .collect()
+    };
+    // Keep the next move in view
+    let mut state =
+        ListState::default().with_selected(Some(view.done.min(total.saturating_sub(1))));
+    frame.render_stateful_widget(List::new(steps), steps_area, &mut state);
+
+    let mut lines = Vec::new();
+    for (label, dipswitch) in [("From:", &plan.from), ("To:", &plan.to)] {
+        let config = app.profile.config(dipswitch);
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<7}", label), theme.label()),
+            Span::styled(app.profile.positions(dipswitch), theme.text().bold()),
+        ]));
+        for (group, format) in [("G1", config.group1_format), ("G2", config.group2_format)] {
+            lines.push(Line::from(vec![
+                Span::styled(format!("       {}  ", group), theme.label()),
+                Span::styled(format.to_string(), theme.format(&format)),
+            ]));
+        }
+    }
+    if plan.equivalents > 1 {
+        lines.push(Line::styled(
+            format!("Fewest moves of {} equivalent settings", plan.equivalents),
+            theme.muted(),
+        ));
+    }
+    frame.render_widget(Paragraph::new(lines), settings_area);
+}
+
 /// Create a Canvas widget for a single switch
-fn create_switch_canvas(position: f64, _state: bool) -> Canvas<'static, impl Fn(&mut ratatui::widgets::canvas::Context)> {
+///
+/// The knob slides left to right, or bottom to top when `vertical`.
+fn create_switch_canvas(
+    theme: &Theme,
+    position: f64,
+    vertical: bool,
+) -> Canvas<'static, impl Fn(&mut ratatui::widgets::canvas::Context)> {
+    let color = theme.switch_color(position);
+    let fill = theme.switch_color(1.0);
+    let (x_bounds, y_bounds) = if vertical {
+        ([0.0, 50.0], [0.0, 100.0])
+    } else {
+        ([0.0, 100.0], [0.0, 50.0])
+    };
     Canvas::default()
         .marker(Marker::Braille)
-        .x_bounds([0.0, 100.0])
-        .y_bounds([0.0, 50.0])
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
         .paint(move |ctx| {
             // Calculate circle position based on animation
-            let circle_x = 10.0 + (position * 80.0);
+            let travel = 10.0 + (position * 80.0);
+            let (circle_x, circle_y) = if vertical {
+                (25.0, travel)
+            } else {
+                (travel, 25.0)
+            };
 
-            // Calculate color gradient from gray to green
             let fill_amount = position;
-            let red = (128.0 - fill_amount * 128.0) as u8;
-            let green = (128.0 + fill_amount * 127.0) as u8;
-            let blue = (128.0 - fill_amount * 128.0) as u8;
-            let color = Color::Rgb(red, green, blue);
 
             // Draw filled circle
             ctx.draw(&Circle {
                 x: circle_x,
-                y: 25.0,
+                y: circle_y,
                 radius: 8.0,
                 color,
             });
@@ -170,94 +958,1314 @@ fn create_switch_canvas(position: f64, _state: bool) -> Canvas<'static, impl Fn(
                 for r in (0..=(6.0 * fill_amount) as u32).step_by(1) {
                     ctx.draw(&Circle {
                         x: circle_x,
-                        y: 25.0,
+                        y: circle_y,
                         radius: r as f64,
-                        color: Color::Green,
+                        color: fill,
                     });
                 }
             }
         })
 }
 
+/// Render the H-sync region of a group's reference signal as an oscilloscope
+/// trace (bi-level sync and burst for SD, tri-level sync for HD)
+fn render_scope(frame: &mut Frame, area: Rect, app: &App, hits: &mut HitMap) {
+    let theme = &app.theme;
+    let config = app.profile.config(&app.dipswitch);
+    let format = [config.group1_format, config.group2_format][app.scope.group];
+    let color = theme.format_color(&format);
+    let grid = theme.grid();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "SCOPE - Group {}: {}",
+            app.scope.group + 1,
+            format.name()
+        ))
+        .title_style(theme.title());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [canvas_area, notes_area] =
+        Layout::vertical([Constraint::Min(8), Constraint::Length(3)]).areas(inner);
+
+    let waveform =
+        format.reference_waveform(WaveformSpan::Line, Waveform::default_oversample(&format));
+    // Window around 0H in microseconds: sync and burst for SD, the pulse and
+    // some blanking either side for HD
+    let (start, end) = match format.subcarrier_frequency() {
+        Some(_) => (-2.0, 12.0),
+        None => {
+            let t = 1e6 / format.timing().sample_clock.as_f64();
+            (-66.0 * t, 242.0 * t)
+        }
+    };
+    let trace = scope_trace(&waveform, start, end);
+
+    let lowest = trace.iter().map(|&(_, v)| v).fold(0.0, f64::min);
+    let highest = trace.iter().map(|&(_, v)| v).fold(0.0, f64::max);
+    let step = 1e6 / waveform.sample_rate;
+    let label = |text: String| Span::styled(text, theme.label());
+    let notes = match format.subcarrier_frequency() {
+        Some(subcarrier) => {
+            let width = trace.iter().filter(|&&(_, v)| v < lowest / 2.0).count() as f64 * step;
+            vec![
+                Line::from(vec![
+                    Span::styled(" Sync   ", theme.muted()),
+                    label(format!("{:.0} mV, {:.2} us wide", lowest, width)),
+                ]),
+                Line::from(vec![
+                    Span::styled(" Burst  ", theme.muted()),
+                    label(format!(
+                        "{:.0} mV p-p at {:.6} MHz",
+                        2.0 * highest,
+                        subcarrier / 1e6
+                    )),
+                ]),
+            ]
+        }
+        None => {
+            let t = 1e6 / format.timing().sample_clock.as_f64();
+            vec![
+                Line::from(vec![
+                    Span::styled(" Sync   ", theme.muted()),
+                    label(format!(
+                        "{:.0}/+{:.0} mV, 44T ({:.3} us) per polarity",
+                        lowest,
+                        highest,
+                        44.0 * t
+                    )),
+                ]),
+                Line::styled(
+                    " 0H at the zero crossing of the tri-level pulse",
+                    theme.muted(),
+                ),
+            ]
+        }
+    };
+
+    let canvas = Canvas::default()
+        .marker(Marker::Braille)
+        .x_bounds([start, end])
+        .y_bounds([-SCOPE_RANGE_MV, SCOPE_RANGE_MV])
+        .paint(move |ctx| {
+            // Blanking level and the 0H timing reference
+            ctx.draw(&CanvasLine::new(start, 0.0, end, 0.0, grid));
+            ctx.draw(&CanvasLine::new(
+                0.0,
+                -SCOPE_RANGE_MV,
+                0.0,
+                SCOPE_RANGE_MV,
+                grid,
+            ));
+            for pair in trace.windows(2) {
+                let [(x1, y1), (x2, y2)] = [pair[0], pair[1]];
+                ctx.draw(&CanvasLine::new(x1, y1, x2, y2, color));
+            }
+            let muted = theme.muted();
+            ctx.print(0.0, SCOPE_RANGE_MV, Span::styled(" 0H", muted));
+            ctx.print(start, lowest, label(format!("{:.0} mV", lowest)));
+            ctx.print(
+                start,
+                highest.max(40.0),
+                label(format!("+{:.0} mV", highest)),
+            );
+            ctx.print(
+                start,
+                -SCOPE_RANGE_MV,
+                Span::styled(format!("{:.1} us", start), muted),
+            );
+            let right = end - (end - start) * 0.15;
+            ctx.print(
+                right,
+                -SCOPE_RANGE_MV,
+                Span::styled(format!("{:.1} us", end), muted),
+            );
+        });
+    frame.render_widget(canvas, canvas_area);
+
+    frame.render_widget(Paragraph::new(notes), notes_area);
+    let [_, _, group_area] = Layout::vertical([Constraint::Length(1); 3]).areas(notes_area);
+    let group_hint = [KeyHint::new(
+        "[G]",
+        &format!("Show group {}", 2 - app.scope.group),
+        KeyCode::Char('g'),
+    )];
+    render_key_hints(frame, group_area, theme, &group_hint, false, hits);
+}
+
+/// (time in us relative to 0H, mV) points of a one-line waveform between
+/// `start` and `end`, wrapping around the line
+fn scope_trace(waveform: &Waveform, start: f64, end: f64) -> Vec<(f64, f64)> {
+    let rate = waveform.sample_rate / 1e6;
+    let reference = (waveform.timing_reference() * waveform.sample_rate).round() as isize;
+    let len = waveform.samples.len() as isize;
+    ((start * rate).round() as isize..=(end * rate).round() as isize)
+        .map(|i| {
+            let sample = waveform.samples[(reference + i).rem_euclid(len) as usize];
+            (i as f64 / rate, sample as f64)
+        })
+        .collect()
+}
+
 /// Render configuration display section
 fn render_config(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = &app.theme;
     let block = Block::default()
         .borders(Borders::ALL)
         .title("CURRENT CONFIGURATION")
-        .title_style(Style::default().fg(Color::Cyan).bold());
+        .title_style(theme.title());
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let config = app.dipswitch.get_config();
+    let config = app.profile.config(&app.dipswitch);
+    let audio = config.audio_reference();
+    let bitmask = app.dipswitch.to_raw() & app.profile.format_mask();
 
     // Determine colors based on format type
-    let group1_color = if config.group1_format.starts_with("HD") {
-        Color::Green
-    } else {
-        Color::Yellow
-    };
-
-    let group2_color = if config.group2_format.starts_with("HD") {
-        Color::Green
-    } else {
-        Color::Yellow
-    };
+    let group1_style = theme.format(&config.group1_format);
+    let group2_style = theme.format(&config.group2_format);
 
     let lines = vec![
         Line::from(vec![
             Span::raw("  Group 1 (Out 1-4):  "),
-            Span::styled(&config.group1_format, Style::default().fg(group1_color)),
+            Span::styled(config.group1_format.to_string(), group1_style),
         ]),
         Line::from(vec![
             Span::raw("  Group 2 (Out 5-6):  "),
-            Span::styled(&config.group2_format, Style::default().fg(group2_color)),
+            Span::styled(config.group2_format.to_string(), group2_style),
         ]),
         Line::from(vec![
             Span::raw("  SD Output:          "),
-            Span::styled(config.sd_video.as_str(), Style::default().fg(Color::White)),
+            Span::styled(config.sd_video.as_str(), theme.text()),
         ]),
         Line::from(vec![
             Span::raw("  Audio (AES-11):     "),
-            Span::styled(config.audio.as_str(), Style::default().fg(Color::White)),
+            Span::styled(audio_description(&audio), theme.text()),
         ]),
-        Line::raw(""),
+        audio_cadence_line(theme, "  Samples/frame G1:   ", &audio.groups[0]),
+        audio_cadence_line(theme, "  Samples/frame G2:   ", &audio.groups[1]),
         Line::from(vec![
             Span::raw("  Video Format Bitmask: "),
             Span::styled(
-                app.dipswitch.get_bitmask_binary(),
-                Style::default().fg(Color::White),
+                format!("{:#010b}", bitmask),
+                theme.text(),
             ),
             Span::raw("  "),
             Span::styled(
-                app.dipswitch.get_bitmask_hex(),
-                Style::default().fg(Color::Cyan),
+                format!("{:#04X}", bitmask),
+                theme.accent(),
+            ),
+        ]),
+        Line::styled("                        87654321", theme.muted()),
+    ];
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// AES-11 output summary, e.g. "Tone 1 kHz at -20 dBFS, 48 kHz"
+fn audio_description(audio: &AudioReference) -> String {
+    let rate = format!("{} kHz", audio.sample_rate as f64 / 1000.0);
+    match audio.tone {
+        Some(tone) => format!(
+            "Tone {} kHz at {} dBFS, {}",
+            tone.frequency_hz as f64 / 1000.0,
+            tone.level_dbfs,
+            rate
+        ),
+        None => format!("Silent, {}", rate),
+    }
+}
+
+/// Samples-per-frame sequence of a group with its frame lock state
+fn audio_cadence_line<'a>(theme: &Theme, label: &'a str, group: &GroupAudio) -> Line<'a> {
+    let (lock, style) = if group.frame_locked {
+        ("  locked", theme.good())
+    } else {
+        ("  not locked", theme.bad())
+    };
+    Line::from(vec![
+        Span::raw(label),
+        Span::styled(group.cadence_name(), theme.text()),
+        Span::styled(lock, style),
+    ])
+}
+
+/// Render the signal timing of both group formats side by side
+fn render_timing(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = &app.theme;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("SIGNAL TIMING")
+        .title_style(theme.title());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let config = app.profile.config(&app.dipswitch);
+    let formats = [config.group1_format, config.group2_format];
+    let timings = formats.map(|format| format.timing());
+
+    let row = |label: &str, values: [String; 2]| {
+        Line::from(vec![
+            Span::styled(format!(" {:<14}", label), theme.muted()),
+            Span::styled(format!("{:<24}", values[0]), theme.text()),
+            Span::styled(values[1].clone(), theme.text()),
+        ])
+    };
+    let lines = vec![
+        Line::from(vec![
+            Span::raw(format!(" {:<14}", "")),
+            Span::styled(
+                format!("{:<24}", "Group 1"),
+                theme.format(&formats[0]).bold(),
             ),
+            Span::styled("Group 2", theme.format(&formats[1]).bold()),
         ]),
-        Line::styled("                        87654321", Style::default().fg(Color::DarkGray)),
+        row("Standard", timings.map(|t| t.standard.as_str().to_string())),
+        row(
+            "Lines",
+            timings.map(|t| format!("{} ({} active)", t.total_lines, t.active_lines)),
+        ),
+        row(
+            "Samples/line",
+            timings.map(|t| format!("{} ({} active)", t.samples_per_line, t.active_samples)),
+        ),
+        row("Sample clock", timings.map(|t| t.sample_clock_name())),
+        row(
+            "Frame period",
+            timings.map(|t| format!("{} s", t.frame_period)),
+        ),
+        row(
+            "Field period",
+            timings.map(|t| {
+                t.field_period
+                    .map_or("-".to_string(), |p| format!("{} s", p))
+            }),
+        ),
+        row("Sync", timings.map(|t| t.sync.as_str().to_string())),
     ];
 
     frame.render_widget(Paragraph::new(lines), inner);
 }
 
+/// Render frame alignment and pulldown between the two groups
+fn render_cadence(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = &app.theme;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("GROUP CADENCE")
+        .title_style(theme.title());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let cadence = app.profile.config(&app.dipswitch).cadence();
+    let mut first = vec![
+        Span::raw("  Frames align "),
+        Span::styled(cadence.alignment_summary(), theme.text()),
+    ];
+    if let Some(pulldown) = &cadence.pulldown {
+        first.push(Span::raw(format!(
+            "  |  Group {} ",
+            pulldown.source_group + 1
+        )));
+        first.push(Span::styled(pulldown.to_string(), theme.text()));
+    }
+
+    let second = match cadence.warnings.first() {
+        Some(warning) => Line::from(vec![
+            Span::styled("  ! ", theme.bad().bold()),
+            Span::styled(warning.to_string(), theme.bad()),
+        ]),
+        None => Line::styled("  Groups can be genlocked together", theme.good()),
+    };
+
+    // Wraps in the narrow side column of the wide layout
+    frame.render_widget(
+        Paragraph::new(vec![Line::from(first), second]).wrap(Wrap { trim: false }),
+        inner,
+    );
+}
+
+/// Block for a popup column, highlighted when it has focus
+fn focus_block<'a>(theme: &Theme, title: &'a str, focused: bool) -> Block<'a> {
+    let style = if focused {
+        theme.accent()
+    } else {
+        theme.muted()
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+        .title_style(style.bold())
+}
+
+/// Render the reverse lookup popup (desired formats → switch settings)
+fn render_solver(frame: &mut Frame, area: Rect, app: &App, hits: &mut HitMap) {
+    let theme = &app.theme;
+    let solver = &app.solver;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("FIND SWITCH SETTINGS")
+        .title_style(theme.title());
+
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let [lists_area, help_area] =
+        Layout::vertical([Constraint::Min(5), Constraint::Length(1)]).areas(inner);
+    let [group1_area, group2_area, results_area] = Layout::horizontal([
+        Constraint::Length(34),
+        Constraint::Length(34),
+        Constraint::Min(24),
+    ])
+    .areas(lists_area);
+
+    let highlight = theme.selected();
+
+    for (title, area, selected, focus) in [
+        (
+            "Group 1 (Out 1-4)",
+            group1_area,
+            solver.group1,
+            SolverFocus::Group1,
+        ),
+        (
+            "Group 2 (Out 5-6)",
+            group2_area,
+            solver.group2,
+            SolverFocus::Group2,
+        ),
+    ] {
+        let items: Vec<ListItem> = solver
+            .targets
+            .iter()
+            .map(|target| {
+                let style = match target {
+                    GroupTarget::Format(format) => theme.format(format),
+                    GroupTarget::Sd => theme.format(&VideoFormat::sd_625()),
+                };
+                ListItem::new(target.to_string()).style(style)
+            })
+            .collect();
+        let list = List::new(items)
+            .block(focus_block(theme, title, solver.focus == focus))
+            .highlight_style(highlight);
+        let mut state = ListState::default().with_selected(Some(selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    let numbers: String = (1..=app.profile.switch_count())
+        .map(|n| n.to_string())
+        .collect();
+    let results_title = format!("Settings ({})", numbers);
+    let results_block = focus_block(theme, &results_title, solver.focus == SolverFocus::Results);
+    if solver.solutions.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No switch setting produces this combination")
+                .style(theme.bad())
+                .block(results_block),
+            results_area,
+        );
+    } else {
+        let items: Vec<ListItem> = solver
+            .solutions
+            .iter()
+            .map(|solution| {
+                let mut spans = vec![Span::styled(
+                    app.profile.positions(&solution.dipswitch),
+                    theme.text(),
+                )];
+                if solution.canonical {
+                    spans.push(Span::styled("  canonical", theme.good()));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(results_block)
+            .highlight_style(highlight);
+        let mut state = ListState::default().with_selected(Some(solver.selected));
+        frame.render_stateful_widget(list, results_area, &mut state);
+    }
+
+    let help = [
+        KeyHint::new("[Tab]", "Column", KeyCode::Tab),
+        KeyHint::label("[Up/Down]", "Select"),
+        KeyHint::new("[Enter]", "Apply Setting", KeyCode::Enter),
+        KeyHint::new("[M]", "Plan Moves", KeyCode::Char('m')),
+        KeyHint::new("[Esc]", "Close", KeyCode::Esc),
+    ];
+    render_key_hints(frame, help_area, theme, &help, true, hits);
+}
+
+/// Groups of Table 2 entries carrying the same signal, for formats listed in
+/// more than one cell, in table order
+fn duplicate_groups(profile: &Profile) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (entry, format) in profile.table_entries() {
+        let existing = groups
+            .iter_mut()
+            .find(|group| profile.hd_format_entry(group[0]).same_signal(&format));
+        match existing {
+            Some(group) => group.push(entry),
+            None => groups.push(vec![entry]),
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// Render the Table 2 browser: the format table as a grid of column and row
+/// switches, with the cell selected by the switches marked
+fn render_table(frame: &mut Frame, app: &App, hits: &mut HitMap) {
+    let theme = &app.theme;
+    let profile = &app.profile;
+    let column_switches = profile.table_column_switches();
+    let row_switches = profile.table_row_switches();
+    let (rows, columns) = (
+        1usize << row_switches.len(),
+        1usize << column_switches.len(),
+    );
+    let label_width = (row_switches.len() * 4 + 2) as u16;
+
+    let width = (label_width + TABLE_CELL_WIDTH * columns as u16 + 2).min(frame.area().width);
+    let height = (column_switches.len() + rows + 6) as u16;
+    let [_, area, _] = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Length(width),
+        Constraint::Fill(1),
+    ])
+    .areas(frame.area());
+    let [_, area, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(height),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("TABLE 2 - HD FORMATS")
+        .title_style(theme.title());
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let muted = theme.muted();
+    let mut lines = Vec::new();
+    // Column headings: one line per column switch, its label at the first
+    // column of each run of equal positions
+    for (k, &switch) in column_switches.iter().enumerate() {
+        let spec = &profile.switches[switch];
+        let mut spans = vec![Span::styled(
+            format!(
+                "{:<width$}",
+                format!("SW{} ({})", switch + 1, spec.function),
+                width = label_width as usize
+            ),
+            muted,
+        )];
+        let shift = column_switches.len() - 1 - k;
+        for column in 0..columns {
+            let right = (column >> shift) & 1 == 1;
+            let starts_run = column & ((1 << shift) - 1) == 0;
+            let label = if !starts_run {
+                ""
+            } else if right {
+                spec.right.as_deref().unwrap_or("RIGHT")
+            } else {
+                spec.left.as_deref().unwrap_or("LEFT")
+            };
+            spans.push(Span::styled(
+                format!(" {:<width$}", label, width = TABLE_CELL_WIDTH as usize - 1),
+                theme.text().bold(),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    let row_heading: Vec<String> = row_switches
+        .iter()
+        .rev()
+        .map(|&switch| format!("{:<4}", profile.switches[switch].function))
+        .collect();
+    lines.push(Line::styled(row_heading.concat(), muted));
+
+    let duplicates = duplicate_groups(profile);
+    let current = profile.table_entry(&app.dipswitch);
+    let cursor_entry = profile.table_grid_entry(app.table.row, app.table.column);
+    let cursor_group = duplicates
+        .iter()
+        .position(|group| group.contains(&cursor_entry));
+    let first_row_y = inner.y + lines.len() as u16;
+
+    for row in 0..rows {
+        let bits: String = (0..row_switches.len())
+            .rev()
+            .map(|bit| format!("{:<4}", (row >> bit) & 1))
+            .collect();
+        let mut spans = vec![Span::styled(
+            format!("{:<width$}", bits, width = label_width as usize),
+            muted,
+        )];
+        for column in 0..columns {
+            let entry = profile.table_grid_entry(row, column);
+            let group = duplicates.iter().position(|group| group.contains(&entry));
+            let marker = if entry == current { ">" } else { " " };
+            let tag = group.map_or(String::new(), |g| format!(" {}", (b'a' + g as u8) as char));
+            let name = profile.hd_format_entry(entry).name();
+            let text = format!(
+                "{}{:<width$}",
+                marker,
+                format!("{}{}", name, tag),
+                width = TABLE_CELL_WIDTH as usize - 1
+            );
+
+            let mut style = match group {
+                Some(g) => theme.group(g),
+                None => theme.text(),
+            };
+            if entry == current {
+                style = style.bold();
+            }
+            if group.is_some() && group == cursor_group {
+                style = style.underlined();
+            }
+            if (row, column) == (app.table.row, app.table.column) {
+                style = theme.selected();
+            }
+            spans.push(Span::styled(text, style));
+
+            let x = inner.x + label_width + column as u16 * TABLE_CELL_WIDTH;
+            let cell =
+                Rect::new(x, first_row_y + row as u16, TABLE_CELL_WIDTH, 1).intersection(inner);
+            hits.push(cell, HitTarget::TableCell { row, column });
+        }
+        lines.push(Line::from(spans));
+    }
+
+    // The marker legend gets its own line so the centered key hints below
+    // cannot cover it in a narrow popup
+    lines.push(Line::from(vec![
+        Span::styled(" > ", Style::default().bold()),
+        Span::styled("current setting", muted),
+    ]));
+    let mut legend = vec![Span::styled(" Same signal in several cells: ", muted)];
+    for (g, group) in duplicates.iter().enumerate() {
+        legend.push(Span::styled(
+            format!(
+                "{} {} ({})  ",
+                (b'a' + g as u8) as char,
+                profile.hd_format_entry(group[0]).name(),
+                group.len()
+            ),
+            theme.group(g),
+        ));
+    }
+    lines.push(Line::from(legend));
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    let help_area = Rect::new(
+        inner.x,
+        inner.y + inner.height.saturating_sub(1),
+        inner.width,
+        1,
+    );
+    let help = [
+        KeyHint::label("[Arrows]", "Move"),
+        KeyHint::new("[Enter]", "Set Switches", KeyCode::Enter),
+        KeyHint::new("[Esc]", "Close", KeyCode::Esc),
+    ];
+    render_key_hints(frame, help_area, theme, &help, true, hits);
+}
+
+/// Render the format search popup: query, ranked matches and the switch
+/// setting each match would produce for the chosen group
+fn render_search(frame: &mut Frame, area: Rect, app: &App, hits: &mut HitMap) {
+    let theme = &app.theme;
+    let search = &app.search;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("FIND FORMAT - Group {}", search.group + 1))
+        .title_style(theme.title());
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let [query_area, results_area, help_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(inner);
+    let query = Line::from(vec![
+        Span::styled(" / ", theme.key()),
+        Span::styled(search.query.clone(), theme.text()),
+        Span::styled("_", theme.accent().slow_blink()),
+    ]);
+    let hint = Line::styled(
+        "   e.g. 1080p25, 1080/50i, 1080i25, 29.97psf, 720 23.98",
+        theme.muted(),
+    );
+    frame.render_widget(Paragraph::new(vec![query, hint]), query_area);
+
+    let numbers: String = (1..=app.profile.switch_count())
+        .map(|n| n.to_string())
+        .collect();
+    let results_title = format!("Matches  (sets {})", numbers);
+    let results_block = focus_block(theme, &results_title, true);
+    if search.matches.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No format of this unit matches")
+                .style(theme.bad())
+                .block(results_block),
+            results_area,
+        );
+    } else {
+        let items: Vec<ListItem> = search
+            .matches
+            .iter()
+            .map(|found| {
+                let setting = search.setting_for(&app.profile, &app.dipswitch, found);
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{:<28}", found.format.name()),
+                        theme.format(&found.format),
+                    ),
+                    Span::styled(app.profile.positions(&setting), theme.text()),
+                ];
+                if found.approximate {
+                    spans.push(Span::styled("  nearest rate", theme.muted()));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(results_block)
+            .highlight_style(theme.selected());
+        let mut state = ListState::default().with_selected(Some(search.selected));
+        frame.render_stateful_widget(list, results_area, &mut state);
+    }
+
+    let help = [
+        KeyHint::new(
+            "[Tab]",
+            &format!("Group {}", 2 - search.group),
+            KeyCode::Tab,
+        ),
+        KeyHint::label("[Up/Down]", "Select"),
+        KeyHint::new("[Enter]", "Set Switches", KeyCode::Enter),
+        KeyHint::new("[Esc]", "Close", KeyCode::Esc),
+    ];
+    render_key_hints(frame, help_area, theme, &help, true, hits);
+}
+
+/// Render the undo history: each step's switch positions, the switches it
+/// moved and the resulting group formats, marking the current step
+fn render_history(frame: &mut Frame, area: Rect, app: &App, hits: &mut HitMap) {
+    let theme = &app.theme;
+    let history = &app.history;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "HISTORY - Step {} of {}",
+            history.position() + 1,
+            history.states().len()
+        ))
+        .title_style(theme.title());
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let [list_area, help_area] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(inner);
+    let numbers: String = (1..=app.profile.switch_count())
+        .map(|n| n.to_string())
+        .collect();
+    let heading = format!(
+        "     #  {:<10}{:<14}{:<32}Group 2",
+        numbers, "Moved", "Group 1"
+    );
+
+    let mut previous: Option<&DipSwitch> = None;
+    let items: Vec<ListItem> = history
+        .states()
+        .enumerate()
+        .map(|(step, dipswitch)| {
+            let moved: Vec<String> = match previous {
+                Some(before) => (0..app.profile.switch_count())
+                    .filter(|&i| before.get(i) != dipswitch.get(i))
+                    .map(|i| (i + 1).to_string())
+                    .collect(),
+                None => vec!["start".to_string()],
+            };
+            previous = Some(dipswitch);
+            let config = app.profile.config(dipswitch);
+            let marker = if step == history.position() {
+                " >"
+            } else {
+                "  "
+            };
+            // Steps after the current one are only reachable with redo
+            let undone = step > history.position();
+            let dim = |style: Style| if undone { theme.muted() } else { style };
+            ListItem::new(Line::from(vec![
+                Span::styled(marker, theme.accent().bold()),
+                Span::styled(format!("{:>4}  ", step + 1), dim(theme.text())),
+                Span::styled(
+                    format!("{:<10}", app.profile.positions(dipswitch)),
+                    dim(theme.text()),
+                ),
+                Span::styled(format!("{:<14}", moved.join(",")), dim(theme.label())),
+                Span::styled(
+                    format!("{:<32}", config.group1_format.to_string()),
+                    dim(theme.format(&config.group1_format)),
+                ),
+                Span::styled(
+                    config.group2_format.to_string(),
+                    dim(theme.format(&config.group2_format)),
+                ),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(focus_block(theme, &heading, true))
+        .highlight_style(theme.selected());
+    let mut state = ListState::default().with_selected(Some(app.history_selected));
+    frame.render_stateful_widget(list, list_area, &mut state);
+
+    let help = [
+        KeyHint::label("[Up/Down]", "Select"),
+        KeyHint::new("[Enter]", "Go To Step", KeyCode::Enter),
+        KeyHint::new("[U]", "Undo", KeyCode::Char('u')),
+        KeyHint::label("[Ctrl-R]", "Redo"),
+        KeyHint::new("[Esc]", "Close", KeyCode::Esc),
+    ];
+    render_key_hints(frame, help_area, theme, &help, true, hits);
+}
+
+/// Render the preset picker: saved presets with their switch positions and
+/// group formats, the name being typed for a new preset and the last result
+fn render_presets(frame: &mut Frame, area: Rect, app: &App, hits: &mut HitMap) {
+    let theme = &app.theme;
+    let presets = &app.presets;
+    let title = match &presets.path {
+        Some(path) => format!("PRESETS - {}", path.display()),
+        None => "PRESETS".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_style(theme.title());
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let [list_area, status_area, help_area] = Layout::vertical([
+        Constraint::Min(3),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(inner);
+    let width = presets
+        .store
+        .presets()
+        .iter()
+        .map(|preset| preset.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let numbers: String = (1..=app.profile.switch_count())
+        .map(|n| n.to_string())
+        .collect();
+    let heading = format!(
+        "   {:<width$}  {:<10}{:<32}Group 2",
+        "Name",
+        numbers,
+        "Group 1",
+        width = width
+    );
+
+    if presets.store.is_empty() {
+        frame.render_widget(
+            Paragraph::new("  No presets yet: press A to save the current setting")
+                .style(theme.muted())
+                .block(focus_block(theme, &heading, true)),
+            list_area,
+        );
+    } else {
+        let items: Vec<ListItem> = presets
+            .store
+            .presets()
+            .iter()
+            .map(|preset| {
+                let config = app.profile.config(&preset.dipswitch);
+                // "=" marks the preset matching the current switches
+                let marker = if preset.dipswitch == app.dipswitch {
+                    " = "
+                } else {
+                    "   "
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(marker, theme.accent().bold()),
+                    Span::styled(
+                        format!("{:<width$}  ", preset.name, width = width),
+                        theme.text(),
+                    ),
+                    Span::styled(
+                        format!("{:<10}", app.profile.positions(&preset.dipswitch)),
+                        theme.text(),
+                    ),
+                    Span::styled(
+                        format!("{:<32}", config.group1_format.to_string()),
+                        theme.format(&config.group1_format),
+                    ),
+                    Span::styled(
+                        config.group2_format.to_string(),
+                        theme.format(&config.group2_format),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(focus_block(theme, &heading, presets.naming.is_none()))
+            .highlight_style(theme.selected());
+        let mut state = ListState::default().with_selected(Some(presets.selected));
+        frame.render_stateful_widget(list, list_area, &mut state);
+    }
+
+    let status = match (&presets.naming, &presets.message) {
+        (Some(name), _) => Line::from(vec![
+            Span::styled(
+                format!(" Save {} as: ", app.profile.positions(&app.dipswitch)),
+                theme.key(),
+            ),
+            Span::styled(name.clone(), theme.text()),
+            Span::styled("_", theme.accent().slow_blink()),
+        ]),
+        (None, Some(message)) => Line::styled(format!(" {}", message), theme.label()),
+        (None, None) => Line::default(),
+    };
+    frame.render_widget(Paragraph::new(status), status_area);
+
+    let help = if presets.naming.is_some() {
+        vec![
+            KeyHint::new("[Enter]", "Save", KeyCode::Enter),
+            KeyHint::new("[Esc]", "Cancel", KeyCode::Esc),
+        ]
+    } else {
+        vec![
+            KeyHint::label("[Up/Down]", "Select"),
+            KeyHint::new("[Enter]", "Set Switches", KeyCode::Enter),
+            KeyHint::new("[A]", "Save Current", KeyCode::Char('a')),
+            KeyHint::new("[D]", "Delete", KeyCode::Char('d')),
+            KeyHint::new("[Esc]", "Close", KeyCode::Esc),
+        ]
+    };
+    render_key_hints(frame, help_area, theme, &help, true, hits);
+}
+
+/// Key hint in a help bar: "[R] Reset"
+struct KeyHint {
+    key: String,
+    action: String,
+    /// Key sent when the hint is clicked, if it stands for a single key
+    code: Option<KeyCode>,
+}
+
+impl KeyHint {
+    fn new(key: &str, action: &str, code: KeyCode) -> Self {
+        Self {
+            key: key.to_string(),
+            action: action.to_string(),
+            code: Some(code),
+        }
+    }
+
+    /// Hint for a group of keys, not clickable
+    fn label(key: &str, action: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            action: action.to_string(),
+            code: None,
+        }
+    }
+}
+
+/// Hints laid out in `width`: on one line separated by "|" if they fit,
+/// else as many per line as fit, separated by spaces
+fn hint_lines(hints: &[KeyHint], width: u16) -> (Vec<&[KeyHint]>, &'static str) {
+    let hint_width = |hint: &KeyHint| hint.key.len() + 1 + hint.action.len();
+    let one_line: usize =
+        hints.iter().map(hint_width).sum::<usize>() + 5 * hints.len().saturating_sub(1);
+    if one_line <= width as usize {
+        return (vec![hints], "  |  ");
+    }
+    let mut lines = Vec::new();
+    let (mut start, mut line_width) = (0, 0);
+    for (i, hint) in hints.iter().enumerate() {
+        if i > start && line_width + 2 + hint_width(hint) > width as usize {
+            lines.push(&hints[start..i]);
+            (start, line_width) = (i, 0);
+        }
+        line_width += if i > start { 2 } else { 0 } + hint_width(hint);
+    }
+    if start < hints.len() {
+        lines.push(&hints[start..]);
+    }
+    (lines, "  ")
+}
+
+/// Render key hints in `area`, wrapped onto more lines if they do not fit on
+/// one, recording each hint (key and action) as clickable
+fn render_key_hints(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    hints: &[KeyHint],
+    centered: bool,
+    hits: &mut HitMap,
+) {
+    let (lines, separator) = hint_lines(hints, area.width);
+    for (row, line) in lines.into_iter().enumerate().take(area.height as usize) {
+        // Popups squeezed by a tiny terminal can place the area off screen
+        let line_area =
+            Rect::new(area.x, area.y + row as u16, area.width, 1).intersection(frame.area());
+        if line_area.is_empty() {
+            break;
+        }
+        render_hint_line(frame, line_area, theme, line, separator, centered, hits);
+    }
+}
+
+/// Render key hints on one line, recording each as clickable
+fn render_hint_line(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    hints: &[KeyHint],
+    separator: &str,
+    centered: bool,
+    hits: &mut HitMap,
+) {
+    let mut spans = Vec::new();
+    let mut extents = Vec::new();
+    let mut width = 0;
+    for (i, hint) in hints.iter().enumerate() {
+        let action = format!(" {}", hint.action);
+        extents.push((width, (hint.key.len() + action.len()) as u16, hint.code));
+        width += (hint.key.len() + action.len()) as u16;
+        spans.push(Span::styled(hint.key.clone(), theme.key()));
+        spans.push(Span::raw(action));
+        if i + 1 < hints.len() {
+            spans.push(Span::raw(separator.to_string()));
+            width += separator.len() as u16;
+        }
+    }
+
+    // Same offset as a centered Paragraph
+    let offset = if centered {
+        (area.width / 2).saturating_sub(width / 2)
+    } else {
+        0
+    };
+    for (start, len, code) in extents {
+        let Some(code) = code else {
+            continue;
+        };
+        let hint_area = Rect::new(area.x + offset + start, area.y, len, 1).intersection(area);
+        hits.push(hint_area, HitTarget::Key(code));
+    }
+
+    let paragraph = Paragraph::new(Line::from(spans));
+    let paragraph = if centered {
+        paragraph.centered()
+    } else {
+        paragraph
+    };
+    frame.render_widget(paragraph, area);
+}
+
+/// Key hints of the keybindings bar for the current mode: switch editing
+/// keys first, views second
+fn keybinding_hints(app: &App) -> [Vec<KeyHint>; 2] {
+    if app.mode == Mode::Compare {
+        let edit_hints = vec![
+            KeyHint::label(&format!("[1-{}]", app.profile.switch_count()), "Toggle"),
+            KeyHint::label("[Arrows]", "Focus/Set"),
+            KeyHint::new("[Tab]", "Other Bank", KeyCode::Tab),
+            KeyHint::new("[U]", "Undo", KeyCode::Char('u')),
+            KeyHint::new("[Esc]", "Close", KeyCode::Esc),
+        ];
+        let copy_hints = vec![
+            KeyHint::new("[>]", "Copy Unit to Required", KeyCode::Char('>')),
+            KeyHint::new("[<]", "Set Unit to Required", KeyCode::Char('<')),
+            KeyHint::new("[M]", "Plan Moves", KeyCode::Char('m')),
+        ];
+        return [edit_hints, copy_hints];
+    }
+    if app.mode == Mode::Plan {
+        let finished = app.plan.done == app.plan.plan.moves.len();
+        let step_hints = vec![
+            KeyHint::new("[Space]", "Next Move", KeyCode::Char(' ')),
+            KeyHint::new("[Backspace]", "Back", KeyCode::Backspace),
+            KeyHint::new(
+                "[A]",
+                match (app.plan.playing, finished) {
+                    (true, _) => "Pause",
+                    (false, true) => "Replay",
+                    (false, false) => "Play",
+                },
+                KeyCode::Char('a'),
+            ),
+            KeyHint::new("[Esc]", "Close", KeyCode::Esc),
+        ];
+        return [step_hints, Vec::new()];
+    }
+    let edit_hints = vec![
+        KeyHint::label(&format!("[1-{}]", app.profile.switch_count()), "Toggle"),
+        KeyHint::label("[Arrows]", "Focus/Set"),
+        KeyHint::new("[R]", "Reset", KeyCode::Char('r')),
+        KeyHint::new("[U]", "Undo", KeyCode::Char('u')),
+        KeyHint::label("[Ctrl-R]", "Redo"),
+        KeyHint::new("[Q]", "Quit", KeyCode::Char('q')),
+    ];
+    let view_hints = vec![
+        KeyHint::new("[F]", "Find Switches", KeyCode::Char('f')),
+        KeyHint::new("[T]", "Table 2", KeyCode::Char('t')),
+        KeyHint::new("[/]", "Search", KeyCode::Char('/')),
+        KeyHint::new("[S]", "Scope", KeyCode::Char('s')),
+        KeyHint::new("[H]", "History", KeyCode::Char('H')),
+        KeyHint::new("[P]", "Presets", KeyCode::Char('p')),
+        KeyHint::new("[C]", "Compare", KeyCode::Char('c')),
+    ];
+    [edit_hints, view_hints]
+}
+
+/// Rows of the keybindings bar at `width`, borders included: two lines of
+/// hints unless they have to wrap
+fn keybindings_height(app: &App, width: u16) -> u16 {
+    let lines: usize = keybinding_hints(app)
+        .iter()
+        .map(|hints| hint_lines(hints, width.saturating_sub(2)).0.len())
+        .sum();
+    (lines as u16).max(2) + 2
+}
+
 /// Render keybindings help bar
-fn render_keybindings(frame: &mut Frame, area: Rect) {
+fn render_keybindings(frame: &mut Frame, area: Rect, app: &App, hits: &mut HitMap) {
+    let theme = &app.theme;
     let block = Block::default()
         .borders(Borders::ALL)
         .title("KEYBINDINGS")
-        .title_style(Style::default().fg(Color::Cyan).bold());
+        .title_style(theme.title());
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let text = Line::from(vec![
-        Span::styled("[1-8]", Style::default().fg(Color::Yellow).bold()),
-        Span::raw(" Toggle Switch  |  "),
-        Span::styled("[R]", Style::default().fg(Color::Yellow).bold()),
-        Span::raw(" Reset  |  "),
-        Span::styled("[Q]", Style::default().fg(Color::Yellow).bold()),
-        Span::raw(" Quit"),
-    ]);
+    // Switch editing keys first, views below
+    let [edit_hints, view_hints] = keybinding_hints(app);
+    let edit_lines = hint_lines(&edit_hints, inner.width).0.len() as u16;
+    let [edit_area, views_area] =
+        Layout::vertical([Constraint::Length(edit_lines), Constraint::Min(0)]).areas(inner);
+    render_key_hints(frame, edit_area, theme, &edit_hints, true, hits);
+    render_key_hints(frame, views_area, theme, &view_hints, true, hits);
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    /// Every target hit somewhere in the frame rendered for `app`
+    fn rendered_targets(app: &App, width: u16, height: u16) -> Vec<HitTarget> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut hits = HitMap::default();
+        terminal.draw(|frame| hits = render(frame, app)).unwrap();
+        let mut targets: Vec<HitTarget> = (0..height)
+            .flat_map(|row| (0..width).map(move |column| (column, row)))
+            .filter_map(|(column, row)| hits.hit(column, row).map(|(_, target)| target))
+            .collect();
+        targets.dedup();
+        targets
+    }
+
+    #[test]
+    fn test_layout_thresholds() {
+        // Eight full-size switch rows need 34 rows, plus 4 for the keys
+        assert_eq!(LayoutMode::for_size(140, 38, 8), LayoutMode::Wide);
+        assert_eq!(LayoutMode::for_size(200, 60, 8), LayoutMode::Wide);
+        assert_eq!(LayoutMode::for_size(139, 60, 8), LayoutMode::Standard);
+        assert_eq!(LayoutMode::for_size(140, 37, 8), LayoutMode::Horizontal);
+
+        // Stacked: the rows need 18 more rows for the details and keys
+        assert_eq!(LayoutMode::for_size(100, 52, 8), LayoutMode::Standard);
+        assert_eq!(LayoutMode::for_size(100, 51, 8), LayoutMode::Horizontal);
+        assert_eq!(LayoutMode::for_size(74, 30, 8), LayoutMode::Horizontal);
+        assert_eq!(LayoutMode::for_size(73, 30, 8), LayoutMode::Compact);
+        assert_eq!(LayoutMode::for_size(74, 29, 8), LayoutMode::Compact);
+        assert_eq!(LayoutMode::for_size(80, 24, 8), LayoutMode::Compact);
+    }
+
+    #[test]
+    fn test_layout_follows_switch_count() {
+        assert_eq!(LayoutMode::for_size(140, 22, 4), LayoutMode::Wide);
+        assert_eq!(LayoutMode::for_size(100, 36, 4), LayoutMode::Standard);
+        assert_eq!(LayoutMode::for_size(38, 30, 4), LayoutMode::Horizontal);
+        assert_eq!(LayoutMode::for_size(140, 22, 8), LayoutMode::Compact);
+    }
+
+    #[test]
+    fn test_compare_is_never_wide() {
+        assert_eq!(
+            LayoutMode::for_mode(160, 60, 8, Mode::Normal),
+            LayoutMode::Wide
+        );
+        assert_eq!(
+            LayoutMode::for_mode(160, 60, 8, Mode::Compare),
+            LayoutMode::Standard
+        );
+        assert_eq!(
+            LayoutMode::for_mode(160, 40, 8, Mode::Plan),
+            LayoutMode::Wide
+        );
+        assert_eq!(
+            LayoutMode::for_mode(160, 40, 8, Mode::Compare),
+            LayoutMode::Horizontal
+        );
+        assert_eq!(
+            LayoutMode::for_mode(80, 24, 8, Mode::Compare),
+            LayoutMode::Compact
+        );
+    }
+
+    #[test]
+    fn test_hit_resolves_regions() {
+        let mut hits = HitMap::default();
+        let switch = Rect::new(2, 1, 10, 4);
+        hits.push(switch, HitTarget::Switch(0));
+        hits.push(Rect::new(2, 5, 10, 4), HitTarget::Switch(1));
+
+        assert_eq!(hits.hit(2, 1), Some((switch, HitTarget::Switch(0))));
+        assert_eq!(
+            hits.hit(11, 4).map(|(_, target)| target),
+            Some(HitTarget::Switch(0))
+        );
+        assert_eq!(
+            hits.hit(11, 5).map(|(_, target)| target),
+            Some(HitTarget::Switch(1))
+        );
+        // Right and bottom edges are outside the region
+        assert_eq!(hits.hit(12, 1), None);
+        assert_eq!(hits.hit(2, 9), None);
+        assert_eq!(hits.hit(0, 0), None);
+    }
+
+    #[test]
+    fn test_later_regions_win() {
+        let mut hits = HitMap::default();
+        hits.push(Rect::new(0, 0, 20, 10), HitTarget::Switch(0));
+        // A popup over the switch, and a key hint inside the popup
+        let popup = Rect::new(5, 2, 10, 6);
+        hits.push(popup, HitTarget::TableCell { row: 1, column: 2 });
+        hits.push(Rect::new(6, 7, 4, 1), HitTarget::Key(KeyCode::Esc));
+
+        assert_eq!(
+            hits.hit(1, 1).map(|(_, target)| target),
+            Some(HitTarget::Switch(0))
+        );
+        assert_eq!(
+            hits.hit(5, 2),
+            Some((popup, HitTarget::TableCell { row: 1, column: 2 }))
+        );
+        assert_eq!(
+            hits.hit(7, 7).map(|(_, target)| target),
+            Some(HitTarget::Key(KeyCode::Esc))
+        );
+        assert_eq!(
+            hits.hit(15, 2).map(|(_, target)| target),
+            Some(HitTarget::Switch(0))
+        );
+    }
 
-    frame.render_widget(Paragraph::new(text).centered(), inner);
+    /// Text of the frame rendered for `app`, one string per row
+    fn rendered_rows(app: &App, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| _ = render(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_table_legend_beside_key_hints() {
+        let mut app = App::new();
+        app.mode = Mode::Table;
+        for width in [100, 60] {
+            let rows = rendered_rows(&app, width, 40);
+            assert!(
+                rows.iter().any(|row| row.contains("> current setting")),
+                "legend hidden at width {}",
+                width
+            );
+            assert!(rows.iter().any(|row| row.contains("[Esc]")));
+        }
+    }
+
+    #[test]
+    fn test_popups_take_all_clicks() {
+        let mut app = App::new();
+        let targets = rendered_targets(&app, 100, 40);
+        for switch in 0..8 {
+            assert!(targets.contains(&HitTarget::Switch(switch)));
+        }
+        assert!(targets.contains(&HitTarget::Key(KeyCode::Char('q'))));
+
+        for mode in [
+            Mode::Solver,
+            Mode::Table,
+            Mode::Search,
+            Mode::History,
+            Mode::Presets,
+        ] {
+            app.mode = mode;
+            let targets = rendered_targets(&app, 100, 40);
+            assert!(
+                targets.iter().all(|target| !matches!(
+                    target,
+                    HitTarget::Switch(_) | HitTarget::BankSwitch { .. }
+                )),
+                "{:?} leaves switches clickable",
+                mode
+            );
+            assert!(!targets.contains(&HitTarget::Key(KeyCode::Char('q'))));
+        }
+    }
+
+    #[test]
+    fn test_tiny_terminals_render() {
+        let mut app = App::new();
+        for mode in [
+            Mode::Normal,
+            Mode::Solver,
+            Mode::Table,
+            Mode::Search,
+            Mode::History,
+            Mode::Presets,
+            Mode::Compare,
+            Mode::Plan,
+        ] {
+            app.mode = mode;
+            for (width, height) in [(120, 8), (80, 8), (60, 8), (80, 5), (20, 3), (4, 1)] {
+                rendered_targets(&app, width, height);
+            }
+        }
+    }
 }
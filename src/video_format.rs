@@ -0,0 +1,517 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned when a format or frame rate string cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFormatError(String);
+
+impl ParseFormatError {
+    fn new(input: &str, expected: &str) -> Self {
+        Self(format!("invalid {} '{}'", expected, input))
+    }
+}
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for ParseFormatError {}
+
+/// Broad signal family of a video format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Standard {
+    Sd,
+    Hd,
+}
+
+impl Standard {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Standard::Sd => "SD",
+            Standard::Hd => "HD",
+        }
+    }
+}
+
+/// Scanning structure of a video format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanType {
+    Interlaced,
+    Progressive,
+    /// Progressive segmented frame (transported as two fields)
+    SegmentedFrame,
+}
+
+impl ScanType {
+    /// Suffix used in format names (e.g. the "i" in "1080i50")
+    pub fn as_str(&self) -> &str {
+        match self {
+            ScanType::Interlaced => "i",
+            ScanType::Progressive => "p",
+            ScanType::SegmentedFrame => "psf",
+        }
+    }
+}
+
+/// Exact frame rate as a reduced rational number of frames per second
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameRate {
+    num: u32,
+    den: u32,
+}
+
+impl FrameRate {
+    pub const FPS_23_98: FrameRate = FrameRate::new(24000, 1001);
+    pub const FPS_24: FrameRate = FrameRate::new(24, 1);
+    pub const FPS_25: FrameRate = FrameRate::new(25, 1);
+    pub const FPS_29_97: FrameRate = FrameRate::new(30000, 1001);
+    pub const FPS_30: FrameRate = FrameRate::new(30, 1);
+    pub const FPS_50: FrameRate = FrameRate::new(50, 1);
+    pub const FPS_59_94: FrameRate = FrameRate::new(60000, 1001);
+    pub const FPS_60: FrameRate = FrameRate::new(60, 1);
+
+    /// Create a frame rate of `num / den` frames per second, reduced to lowest terms
+    pub const fn new(num: u32, den: u32) -> Self {
+        assert!(den != 0, "frame rate denominator must be non-zero");
+        let divisor = gcd(num, den);
+        Self {
+            num: num / divisor,
+            den: den / divisor,
+        }
+    }
+
+    pub fn numerator(&self) -> u32 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> u32 {
+        self.den
+    }
+
+    /// Frame rate multiplied by an integer factor (e.g. frame rate → field rate),
+    /// or `None` if the result does not fit
+    pub fn times(&self, factor: u32) -> Option<Self> {
+        let divisor = gcd(factor, self.den);
+        let num = self.num.checked_mul(factor / divisor)?;
+        Some(Self::new(num, self.den / divisor))
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl fmt::Display for FrameRate {
+    /// Formats the rate the way it appears in format names ("25", "59.94", "23.98")
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{:.2}", self.as_f64())
+        }
+    }
+}
+
+impl FromStr for FrameRate {
+    type Err = ParseFormatError;
+
+    /// Parse "25", "59.94", "23.976" or an exact "30000/1001"
+    ///
+    /// Integer values ("25.0") are exact; other fractional rates are taken
+    /// to be the NTSC-related x/1.001 rates.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseFormatError::new(s, "frame rate");
+        let s = s.trim();
+
+        if let Some((num, den)) = s.split_once('/') {
+            let num = num.trim().parse::<u32>().map_err(|_| err())?;
+            let den = den.trim().parse::<u32>().map_err(|_| err())?;
+            if num == 0 || den == 0 {
+                return Err(err());
+            }
+            return Ok(FrameRate::new(num, den));
+        }
+
+        if let Ok(integer) = s.parse::<u32>() {
+            return if integer == 0 {
+                Err(err())
+            } else {
+                Ok(FrameRate::new(integer, 1))
+            };
+        }
+
+        let value = s.parse::<f64>().map_err(|_| err())?;
+        if value.fract() == 0.0 {
+            return if (1.0..=1000.0).contains(&value) {
+                Ok(FrameRate::new(value as u32, 1))
+            } else {
+                Err(err())
+            };
+        }
+        let nominal = (value * 1.001).round();
+        if !(1.0..=1000.0).contains(&nominal) || (nominal / 1.001 - value).abs() > 0.01 {
+            return Err(err());
+        }
+        Ok(FrameRate::new(nominal as u32 * 1000, 1001))
+    }
+}
+
+const fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    if a == 0 { 1 } else { a }
+}
+
+/// A fully described video format produced by one output group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VideoFormat {
+    pub standard: Standard,
+    pub active_lines: u16,
+    pub scan: ScanType,
+    /// Frames (not fields) per second
+    pub frame_rate: FrameRate,
+    /// Table 2 lists this entry together with its PsF equivalent
+    /// (see the NOTE below Table 2, e.g. "1080i59.94 (1080psf29.97)")
+    pub psf_alias: bool,
+}
+
+impl VideoFormat {
+    /// 525-line NTSC-rate SD (486 active lines, 29.97 frames per second)
+    pub const fn sd_525() -> Self {
+        Self {
+            standard: Standard::Sd,
+            active_lines: 486,
+            scan: ScanType::Interlaced,
+            frame_rate: FrameRate::FPS_29_97,
+            psf_alias: false,
+        }
+    }
+
+    /// 625-line PAL-rate SD (576 active lines, 25 frames per second)
+    pub const fn sd_625() -> Self {
+        Self {
+            standard: Standard::Sd,
+            active_lines: 576,
+            scan: ScanType::Interlaced,
+            frame_rate: FrameRate::FPS_25,
+            psf_alias: false,
+        }
+    }
+
+    /// HD format with the given active lines, scan type and frame rate
+    pub const fn hd(active_lines: u16, scan: ScanType, frame_rate: FrameRate) -> Self {
+        Self {
+            standard: Standard::Hd,
+            active_lines,
+            scan,
+            frame_rate,
+            psf_alias: false,
+        }
+    }
+
+    /// Mark this format as listed with its PsF alias
+    pub const fn with_psf_alias(mut self) -> Self {
+        self.psf_alias = true;
+        self
+    }
+
+    pub fn is_hd(&self) -> bool {
+        self.standard == Standard::Hd
+    }
+
+    /// Total lines per frame including blanking (525, 625, 750 or 1125)
+    pub fn total_lines(&self) -> u16 {
+        match (self.standard, self.active_lines) {
+            (Standard::Sd, 486) => 525,
+            (Standard::Sd, _) => 625,
+            (Standard::Hd, 720) => 750,
+            (Standard::Hd, _) => 1125,
+        }
+    }
+
+    /// Rate shown in the format name: field rate for interlaced, frame rate otherwise
+    pub fn display_rate(&self) -> FrameRate {
+        match self.scan {
+            // Parsed interlaced formats always have a representable field rate
+            ScanType::Interlaced => self
+                .frame_rate
+                .times(2)
+                .expect("field rate of an interlaced format fits in u32"),
+            ScanType::Progressive | ScanType::SegmentedFrame => self.frame_rate,
+        }
+    }
+
+    /// The PsF format carried by the same signal, if Table 2 lists one
+    pub fn psf_equivalent(&self) -> Option<VideoFormat> {
+        if self.psf_alias && self.scan == ScanType::Interlaced {
+            Some(VideoFormat::hd(
+                self.active_lines,
+                ScanType::SegmentedFrame,
+                self.frame_rate,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Compare the signal itself, ignoring how Table 2 happens to label it
+    pub fn same_signal(&self, other: &VideoFormat) -> bool {
+        self.standard == other.standard
+            && self.active_lines == other.active_lines
+            && self.scan == other.scan
+            && self.frame_rate == other.frame_rate
+    }
+
+    /// Format name without the SD/HD prefix (e.g. "1080p25", "525i (NTSC)")
+    pub fn name(&self) -> String {
+        match self.standard {
+            Standard::Sd => {
+                let system = if self.total_lines() == 525 {
+                    "NTSC"
+                } else {
+                    "PAL"
+                };
+                format!("{}{} ({})", self.total_lines(), self.scan.as_str(), system)
+            }
+            Standard::Hd => {
+                let name = format!(
+                    "{}{}{}",
+                    self.active_lines,
+                    self.scan.as_str(),
+                    self.display_rate()
+                );
+                match self.psf_equivalent() {
+                    Some(alias) => format!("{} ({})", name, alias.name()),
+                    None => name,
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for VideoFormat {
+    type Err = ParseFormatError;
+
+    /// Parse a format name such as "1080p25", "1080i59.94", "1080psf23.98",
+    /// "720p50", "525i" or "625i" (an "SD - "/"HD - " prefix is accepted)
+    ///
+    /// A trailing PsF alias as printed by `Display`, e.g. "1080i50 (1080psf25)",
+    /// sets `psf_alias`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseFormatError::new(s, "video format");
+        let name = s.trim().to_ascii_lowercase();
+        let name = name
+            .strip_prefix("hd - ")
+            .or_else(|| name.strip_prefix("sd - "))
+            .unwrap_or(&name);
+        let (name, suffix) = match name.split_once(char::is_whitespace) {
+            Some((name, suffix)) => (name, suffix.trim()),
+            None => (name, ""),
+        };
+        let format = parse_format_name(name).ok_or_else(err)?;
+
+        if suffix.is_empty() {
+            return Ok(format);
+        }
+        let alias = suffix
+            .strip_prefix('(')
+            .and_then(|suffix| suffix.strip_suffix(')'))
+            .ok_or_else(err)?;
+        match format.standard {
+            // "(NTSC)" / "(PAL)" are informational only
+            Standard::Sd if matches!(alias, "ntsc" | "pal") => Ok(format),
+            Standard::Sd => Err(err()),
+            Standard::Hd => {
+                let aliased = format.with_psf_alias();
+                let expected = aliased.psf_equivalent().ok_or_else(err)?;
+                if parse_format_name(alias) == Some(expected) {
+                    Ok(aliased)
+                } else {
+                    Err(err())
+                }
+            }
+        }
+    }
+}
+
+/// Parse a bare format name such as "1080i50" (no prefix or alias)
+fn parse_format_name(name: &str) -> Option<VideoFormat> {
+    let digits = name.find(|c: char| !c.is_ascii_digit())?;
+    let lines: u16 = name[..digits].parse().ok()?;
+    let rest = &name[digits..];
+    let (scan, rate) = if let Some(rate) = rest.strip_prefix("psf") {
+        (ScanType::SegmentedFrame, rate)
+    } else if let Some(rate) = rest.strip_prefix('i') {
+        (ScanType::Interlaced, rate)
+    } else if let Some(rate) = rest.strip_prefix('p') {
+        (ScanType::Progressive, rate)
+    } else {
+        return None;
+    };
+
+    match (lines, scan) {
+        (525 | 480 | 486, ScanType::Interlaced) if rate.is_empty() || rate == "59.94" => {
+            return Some(VideoFormat::sd_525());
+        }
+        (625 | 576, ScanType::Interlaced) if rate.is_empty() || rate == "50" => {
+            return Some(VideoFormat::sd_625());
+        }
+        // 720-line HD is progressive only
+        (720, ScanType::Progressive) | (1080, _) => {}
+        _ => return None,
+    }
+
+    let display_rate: FrameRate = rate.parse().ok()?;
+    let frame_rate = match scan {
+        ScanType::Interlaced => FrameRate::new(
+            display_rate.numerator(),
+            display_rate.denominator().checked_mul(2)?,
+        ),
+        ScanType::Progressive | ScanType::SegmentedFrame => display_rate,
+    };
+    Some(VideoFormat::hd(lines, scan, frame_rate))
+}
+
+impl fmt::Display for VideoFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.standard.as_str(), self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_rate_reduced() {
+        assert_eq!(FrameRate::new(50, 2), FrameRate::FPS_25);
+        assert_eq!(FrameRate::FPS_29_97.times(2), Some(FrameRate::FPS_59_94));
+        assert_eq!(
+            FrameRate::new(u32::MAX, 2).times(2),
+            Some(FrameRate::new(u32::MAX, 1))
+        );
+        assert_eq!(FrameRate::new(u32::MAX, 1).times(2), None);
+        assert_eq!(FrameRate::FPS_23_98.numerator(), 24000);
+        assert_eq!(FrameRate::FPS_23_98.denominator(), 1001);
+    }
+
+    #[test]
+    fn test_frame_rate_display() {
+        assert_eq!(FrameRate::FPS_23_98.to_string(), "23.98");
+        assert_eq!(FrameRate::FPS_59_94.to_string(), "59.94");
+        assert_eq!(FrameRate::FPS_50.to_string(), "50");
+    }
+
+    #[test]
+    fn test_sd_display() {
+        assert_eq!(VideoFormat::sd_525().to_string(), "SD - 525i (NTSC)");
+        assert_eq!(VideoFormat::sd_625().to_string(), "SD - 625i (PAL)");
+    }
+
+    #[test]
+    fn test_hd_display() {
+        let interlaced = VideoFormat::hd(1080, ScanType::Interlaced, FrameRate::FPS_25);
+        assert_eq!(interlaced.to_string(), "HD - 1080i50");
+        assert_eq!(
+            interlaced.with_psf_alias().to_string(),
+            "HD - 1080i50 (1080psf25)"
+        );
+
+        let psf = VideoFormat::hd(1080, ScanType::SegmentedFrame, FrameRate::FPS_23_98);
+        assert_eq!(psf.to_string(), "HD - 1080psf23.98");
+    }
+
+    #[test]
+    fn test_frame_rate_parse() {
+        assert_eq!("25".parse(), Ok(FrameRate::FPS_25));
+        assert_eq!("59.94".parse(), Ok(FrameRate::FPS_59_94));
+        assert_eq!("23.976".parse(), Ok(FrameRate::FPS_23_98));
+        assert_eq!("30000/1001".parse(), Ok(FrameRate::FPS_29_97));
+        assert_eq!("24.0".parse(), Ok(FrameRate::new(24, 1)));
+        assert_eq!("25.0".parse(), Ok(FrameRate::FPS_25));
+        assert_eq!("50.00".parse(), Ok(FrameRate::FPS_50));
+        assert!("0.0".parse::<FrameRate>().is_err());
+        assert!("0".parse::<FrameRate>().is_err());
+        assert!("25.5".parse::<FrameRate>().is_err());
+        assert!("fast".parse::<FrameRate>().is_err());
+    }
+
+    #[test]
+    fn test_format_parse() {
+        assert_eq!(
+            "1080p25".parse(),
+            Ok(VideoFormat::hd(
+                1080,
+                ScanType::Progressive,
+                FrameRate::FPS_25
+            ))
+        );
+        assert_eq!(
+            "1080i59.94".parse(),
+            Ok(VideoFormat::hd(
+                1080,
+                ScanType::Interlaced,
+                FrameRate::FPS_29_97
+            ))
+        );
+        assert_eq!(
+            "HD - 1080psf23.98".parse(),
+            Ok(VideoFormat::hd(
+                1080,
+                ScanType::SegmentedFrame,
+                FrameRate::FPS_23_98
+            ))
+        );
+        assert_eq!("525i".parse(), Ok(VideoFormat::sd_525()));
+        assert_eq!("SD - 625i (PAL)".parse(), Ok(VideoFormat::sd_625()));
+        assert!("1080x25".parse::<VideoFormat>().is_err());
+        assert!("900p25".parse::<VideoFormat>().is_err());
+        assert_eq!(
+            "1080p25.00".parse(),
+            Ok(VideoFormat::hd(
+                1080,
+                ScanType::Progressive,
+                FrameRate::FPS_25
+            ))
+        );
+        assert!("720i50".parse::<VideoFormat>().is_err());
+        assert!("720psf25".parse::<VideoFormat>().is_err());
+        // The field rate denominator doubles and must not overflow
+        assert!("1080i1/4294967295".parse::<VideoFormat>().is_err());
+        assert_eq!(
+            "1080i4294967295"
+                .parse::<VideoFormat>()
+                .map(|format| format.display_rate()),
+            Ok(FrameRate::new(u32::MAX, 1))
+        );
+        assert!("1080i50 (1080psf24)".parse::<VideoFormat>().is_err());
+        assert!("1080p25 (fast)".parse::<VideoFormat>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for format in [
+            VideoFormat::sd_525(),
+            VideoFormat::sd_625(),
+            VideoFormat::hd(720, ScanType::Progressive, FrameRate::FPS_59_94),
+            VideoFormat::hd(1080, ScanType::Interlaced, FrameRate::FPS_25),
+            VideoFormat::hd(1080, ScanType::Interlaced, FrameRate::FPS_29_97).with_psf_alias(),
+        ] {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+    }
+
+    #[test]
+    fn test_same_signal_ignores_alias() {
+        let plain = VideoFormat::hd(1080, ScanType::Interlaced, FrameRate::FPS_25);
+        let aliased = plain.with_psf_alias();
+        assert_ne!(plain, aliased);
+        assert!(plain.same_signal(&aliased));
+    }
+}
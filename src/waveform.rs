@@ -0,0 +1,522 @@
+//! Sampled analog reference waveforms: NTSC/PAL blackburst for SD formats and
+//! tri-level sync for HD formats, with timing taken from the format's
+//! standard (SMPTE 170M / ITU-R BT.470 for SD, SMPTE ST 274 / ST 296 for HD).
+
+use std::f64::consts::TAU;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::video_format::{ScanType, Standard, VideoFormat};
+
+/// How much of the reference signal to synthesize
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveformSpan {
+    /// One active-picture line
+    Line,
+    /// The first field (the whole frame for progressive formats), including
+    /// the vertical interval
+    Field,
+}
+
+impl FromStr for WaveformSpan {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "line" => Ok(WaveformSpan::Line),
+            "field" => Ok(WaveformSpan::Field),
+            _ => Err(format!("unknown span '{}' (expected line or field)", s)),
+        }
+    }
+}
+
+/// File format for an exported waveform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveformFileFormat {
+    /// "time_us,millivolts" rows
+    Csv,
+    /// Little-endian f32 millivolts, one per sample
+    F32,
+}
+
+impl WaveformFileFormat {
+    pub fn as_str(&self) -> &str {
+        match self {
+            WaveformFileFormat::Csv => "csv",
+            WaveformFileFormat::F32 => "f32",
+        }
+    }
+
+    /// Format implied by a file extension (.csv, .f32, .raw)
+    pub fn from_path(path: impl AsRef<Path>) -> Option<WaveformFileFormat> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "raw" => Some(WaveformFileFormat::F32),
+            other => other.parse().ok(),
+        }
+    }
+}
+
+impl fmt::Display for WaveformFileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WaveformFileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(WaveformFileFormat::Csv),
+            "f32" => Ok(WaveformFileFormat::F32),
+            _ => Err(format!(
+                "unknown waveform format '{}' (expected csv or f32)",
+                s
+            )),
+        }
+    }
+}
+
+/// Sampled reference waveform in millivolts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waveform {
+    pub format: VideoFormat,
+    pub span: WaveformSpan,
+    /// Samples per second
+    pub sample_rate: f64,
+    pub samples: Vec<f32>,
+}
+
+/// Level and timing parameters of an SD blackburst signal
+struct Blackburst {
+    /// Sync tip, black (setup) and burst peak in mV
+    sync: f64,
+    black: f64,
+    burst: f64,
+    /// Color subcarrier frequency in Hz
+    subcarrier: f64,
+    /// Times after the sync leading edge (0H) in seconds
+    sync_width: f64,
+    equalizing_width: f64,
+    serration_width: f64,
+    burst_start: f64,
+    burst_cycles: f64,
+    active_start: f64,
+    front_porch: f64,
+    edge: f64,
+    /// Lines (field 1) carrying burst and active black
+    burst_lines: (u16, u16),
+    active_lines: (u16, u16),
+    /// Half-line slots opening field 1 and closing it before field 2
+    opening: &'static [Slot],
+    closing: &'static [Slot],
+}
+
+/// NTSC blackburst, SMPTE 170M (1 IRE = 1/140 V)
+const NTSC: Blackburst = Blackburst {
+    sync: -40.0 * 1000.0 / 140.0,
+    black: 7.5 * 1000.0 / 140.0,
+    burst: 20.0 * 1000.0 / 140.0,
+    subcarrier: 5_000_000.0 * 63.0 / 88.0,
+    sync_width: 4.7e-6,
+    equalizing_width: 2.3e-6,
+    serration_width: 4.7e-6,
+    burst_start: 5.3e-6,
+    burst_cycles: 9.0,
+    active_start: 9.4e-6,
+    front_porch: 1.5e-6,
+    edge: 140e-9,
+    burst_lines: (10, 263),
+    active_lines: (22, 263),
+    opening: &[
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+    ],
+    closing: &[],
+};
+
+/// PAL blackburst, ITU-R BT.470 (B/G)
+const PAL: Blackburst = Blackburst {
+    sync: -300.0,
+    black: 0.0,
+    burst: 150.0,
+    subcarrier: 4_433_618.75,
+    sync_width: 4.7e-6,
+    equalizing_width: 2.35e-6,
+    serration_width: 4.7e-6,
+    burst_start: 5.6e-6,
+    burst_cycles: 10.0,
+    active_start: 10.5e-6,
+    front_porch: 1.65e-6,
+    edge: 200e-9,
+    burst_lines: (7, 310),
+    active_lines: (23, 310),
+    opening: &[
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Broad,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+    ],
+    closing: &[
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+        Slot::Equalizing,
+    ],
+};
+
+/// Tri-level sync amplitude in mV (each polarity)
+const TRI_LEVEL: f64 = 300.0;
+
+/// Lines at the start of each field or frame carrying HD broad pulses
+const HD_SYNC_LINES: u16 = 5;
+
+/// Part of a field, in half-line units
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Equalizing,
+    Broad,
+    /// A whole normal line (by line number)
+    Line(u16),
+    /// The first half of a normal line
+    HalfLine(u16),
+}
+
+impl VideoFormat {
+    /// Synthesize the analog reference signal at `oversample` times the
+    /// format's sample clock
+    pub fn reference_waveform(&self, span: WaveformSpan, oversample: u32) -> Waveform {
+        let timing = self.timing();
+        let sample_rate = timing.sample_clock.as_f64() * oversample.max(1) as f64;
+        let line_period = timing.samples_per_line as f64 / timing.sample_clock.as_f64();
+        let samples = match self.standard {
+            Standard::Sd => {
+                let params = if self.total_lines() == 525 {
+                    &NTSC
+                } else {
+                    &PAL
+                };
+                let slots = sd_slots(params, self.total_lines(), span);
+                render(&slots, sample_rate, line_period, |slot, t, t_field| {
+                    sd_level(params, slot, t, t_field, line_period)
+                })
+            }
+            Standard::Hd => {
+                let slots = hd_slots(self, span);
+                // T is one period of the (non-oversampled) sample clock
+                let t_unit = 1.0 / timing.sample_clock.as_f64();
+                render(&slots, sample_rate, line_period, |slot, t, _| {
+                    hd_level(self, slot, t, t_unit, line_period)
+                })
+            }
+        };
+
+        Waveform {
+            format: *self,
+            span,
+            sample_rate,
+            samples,
+        }
+    }
+}
+
+impl VideoFormat {
+    /// Color subcarrier frequency of the SD blackburst in Hz (none for HD)
+    pub fn subcarrier_frequency(&self) -> Option<f64> {
+        match self.standard {
+            Standard::Sd if self.total_lines() == 525 => Some(NTSC.subcarrier),
+            Standard::Sd => Some(PAL.subcarrier),
+            Standard::Hd => None,
+        }
+    }
+}
+
+impl Waveform {
+    /// Oversampling that resolves the SD subcarrier; HD is sampled at T
+    pub fn default_oversample(format: &VideoFormat) -> u32 {
+        if format.is_hd() { 1 } else { 4 }
+    }
+
+    /// Time of the line timing reference 0H after the start of each line:
+    /// the sync leading edge for SD, the tri-level zero crossing (44T) for HD
+    pub fn timing_reference(&self) -> f64 {
+        match self.format.standard {
+            Standard::Sd => 0.0,
+            Standard::Hd => 44.0 / self.format.timing().sample_clock.as_f64(),
+        }
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate
+    }
+
+    /// "time_us,millivolts" rows with a header
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("time_us,millivolts\n");
+        for (i, sample) in self.samples.iter().enumerate() {
+            let time = i as f64 / self.sample_rate * 1e6;
+            out.push_str(&format!("{:.6},{:.3}\n", time, sample));
+        }
+        out
+    }
+
+    /// Little-endian f32 millivolts
+    pub fn to_f32le(&self) -> Vec<u8> {
+        self.samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+    }
+
+    /// Waveform encoded in the given file format
+    pub fn encode(&self, format: WaveformFileFormat) -> Vec<u8> {
+        match format {
+            WaveformFileFormat::Csv => self.to_csv().into_bytes(),
+            WaveformFileFormat::F32 => self.to_f32le(),
+        }
+    }
+}
+
+/// Sample a sequence of slots; `level` gets the slot, the time into it and
+/// the time since the start of the waveform
+fn render(
+    slots: &[Slot],
+    sample_rate: f64,
+    line_period: f64,
+    level: impl Fn(Slot, f64, f64) -> f64,
+) -> Vec<f32> {
+    let duration = |slot: &Slot| match slot {
+        Slot::Line(_) => line_period,
+        _ => line_period / 2.0,
+    };
+    let total: f64 = slots.iter().map(duration).sum();
+    let count = (total * sample_rate).round() as usize;
+
+    let mut samples = Vec::with_capacity(count);
+    let (mut index, mut slot_start) = (0, 0.0);
+    for i in 0..count {
+        let t_field = i as f64 / sample_rate;
+        while index + 1 < slots.len() && t_field >= slot_start + duration(&slots[index]) {
+            slot_start += duration(&slots[index]);
+            index += 1;
+        }
+        samples.push(level(slots[index], t_field - slot_start, t_field) as f32);
+    }
+    samples
+}
+
+/// 0..1 gate open between `start` and `end` with linear edges of `rise`
+fn gate(t: f64, start: f64, end: f64, rise: f64) -> f64 {
+    let opening = ((t - start) / rise + 0.5).clamp(0.0, 1.0);
+    let closing = ((end - t) / rise + 0.5).clamp(0.0, 1.0);
+    opening.min(closing)
+}
+
+fn sd_slots(params: &Blackburst, total_lines: u16, span: WaveformSpan) -> Vec<Slot> {
+    if span == WaveformSpan::Line {
+        return vec![Slot::Line(params.active_lines.0 + 50)];
+    }
+    // Field 1 is total_lines half-lines long (262.5 or 312.5 lines)
+    let first_line = params.opening.len() as u16 / 2 + 1;
+    let last_line = (total_lines - params.closing.len() as u16) / 2;
+    let mut slots = params.opening.to_vec();
+    slots.extend((first_line..=last_line).map(Slot::Line));
+    slots.extend_from_slice(params.closing);
+    let half_lines =
+        params.opening.len() + 2 * (last_line - first_line + 1) as usize + params.closing.len();
+    if half_lines < total_lines as usize {
+        slots.push(Slot::HalfLine(last_line + 1));
+    }
+    slots
+}
+
+fn sd_level(params: &Blackburst, slot: Slot, t: f64, t_field: f64, line_period: f64) -> f64 {
+    let half = line_period / 2.0;
+    let line = match slot {
+        Slot::Equalizing => {
+            return params.sync * gate(t, 0.0, params.equalizing_width, params.edge);
+        }
+        Slot::Broad => {
+            return params.sync * gate(t, 0.0, half - params.serration_width, params.edge);
+        }
+        Slot::Line(line) | Slot::HalfLine(line) => line,
+    };
+
+    let mut level = params.sync * gate(t, 0.0, params.sync_width, params.edge);
+    if (params.burst_lines.0..=params.burst_lines.1).contains(&line) {
+        let length = params.burst_cycles / params.subcarrier;
+        let envelope = gate(t, params.burst_start, params.burst_start + length, 300e-9);
+        // NTSC burst is at 180 degrees; PAL swings +/-135 degrees line by line
+        let phase = if params.subcarrier < 4e6 {
+            0.5
+        } else if line % 2 == 1 {
+            0.375
+        } else {
+            0.625
+        };
+        level += params.burst * envelope * (TAU * (params.subcarrier * t_field + phase)).sin();
+    }
+    if (params.active_lines.0..=params.active_lines.1).contains(&line) {
+        level += params.black
+            * gate(
+                t,
+                params.active_start,
+                line_period - params.front_porch,
+                params.edge,
+            );
+    }
+    level
+}
+
+fn hd_slots(format: &VideoFormat, span: WaveformSpan) -> Vec<Slot> {
+    if span == WaveformSpan::Line {
+        return vec![Slot::Line(HD_SYNC_LINES + 50)];
+    }
+    let total = format.total_lines();
+    match format.scan {
+        ScanType::Progressive => (1..=total).map(Slot::Line).collect(),
+        // Interlaced and PsF references share the interlaced field structure:
+        // field 1 ends half way through line (total + 1) / 2
+        ScanType::Interlaced | ScanType::SegmentedFrame => {
+            let last = total / 2;
+            let mut slots: Vec<Slot> = (1..=last).map(Slot::Line).collect();
+            slots.push(Slot::HalfLine(last + 1));
+            slots
+        }
+    }
+}
+
+fn hd_level(format: &VideoFormat, slot: Slot, t: f64, t_unit: f64, line_period: f64) -> f64 {
+    let (Slot::Line(line) | Slot::HalfLine(line)) = slot else {
+        return 0.0;
+    };
+    let rise = 4.0 * t_unit;
+    // Tri-level pulse: -300 mV for 44T, then +300 mV for 44T; its zero
+    // crossing is the timing reference 0H, 44T into the line
+    let tri_level = |t: f64| {
+        -TRI_LEVEL * gate(t, 0.0, 44.0 * t_unit, rise)
+            + TRI_LEVEL * gate(t, 44.0 * t_unit, 88.0 * t_unit, rise)
+    };
+
+    if line > HD_SYNC_LINES {
+        return tri_level(t);
+    }
+    // Vertical sync lines: broad pulses from 132T after 0H to 176T before the
+    // next timing reference; interlaced lines carry a second reference mid-line
+    let interval = if format.scan == ScanType::Progressive {
+        line_period
+    } else {
+        line_period / 2.0
+    };
+    let t = t % interval;
+    tri_level(t) - TRI_LEVEL * gate(t, 176.0 * t_unit, interval - 132.0 * t_unit, rise)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(waveform: &Waveform, seconds: f64) -> f32 {
+        waveform.samples[(seconds * waveform.sample_rate).round() as usize]
+    }
+
+    #[test]
+    fn test_ntsc_line() {
+        let line = VideoFormat::sd_525().reference_waveform(WaveformSpan::Line, 4);
+        assert_eq!(line.samples.len(), 858 * 4);
+        // Sync tip at -40 IRE, setup black at 7.5 IRE
+        assert!((at(&line, 2e-6) + 285.714).abs() < 0.01);
+        assert!((at(&line, 30e-6) - 53.571).abs() < 0.01);
+        // Burst peaks near +/-20 IRE
+        let burst = &line.samples
+            [(5.5e-6 * line.sample_rate) as usize..(7.5e-6 * line.sample_rate) as usize];
+        let peak = burst.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 130.0 && peak <= 143.0, "{}", peak);
+        // Blanking between sync and burst
+        assert!(at(&line, 5.0e-6).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_pal_field() {
+        let field = VideoFormat::sd_625().reference_waveform(WaveformSpan::Field, 1);
+        // 312.5 lines of 864 samples
+        assert_eq!(field.samples.len(), 864 * 625 / 2);
+        // Broad pulse at the start of the field, sync level for 27.3 us
+        assert_eq!(at(&field, 20e-6), -300.0);
+        assert!(at(&field, 30e-6).abs() < 0.01);
+        assert!((field.duration() - 0.02).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ntsc_field_length() {
+        let field = VideoFormat::sd_525().reference_waveform(WaveformSpan::Field, 1);
+        assert_eq!(field.samples.len(), 858 * 525 / 2);
+    }
+
+    #[test]
+    fn test_tri_level_line() {
+        let format: VideoFormat = "1080i50".parse().unwrap();
+        let line = format.reference_waveform(WaveformSpan::Line, 1);
+        assert_eq!(line.samples.len(), 2640);
+        assert_eq!(line.samples[20], -300.0);
+        assert_eq!(line.samples[66], 300.0);
+        assert_eq!(line.samples[200], 0.0);
+        // 0H sits on the zero crossing between the two halves of the pulse
+        let reference = (line.timing_reference() * line.sample_rate).round() as usize;
+        assert_eq!(reference, 44);
+        assert!(line.samples[reference].abs() < 1.0);
+    }
+
+    #[test]
+    fn test_hd_field() {
+        let interlaced: VideoFormat = "1080i59.94".parse().unwrap();
+        let field = interlaced.reference_waveform(WaveformSpan::Field, 1);
+        assert_eq!(field.samples.len(), 2200 * 1125 / 2);
+        // Broad pulses on the vertical sync lines, in both halves
+        assert_eq!(field.samples[500], -300.0);
+        assert_eq!(field.samples[1100 + 500], -300.0);
+
+        let progressive: VideoFormat = "720p50".parse().unwrap();
+        let frame = progressive.reference_waveform(WaveformSpan::Field, 1);
+        assert_eq!(frame.samples.len(), 1980 * 750);
+    }
+
+    #[test]
+    fn test_exports() {
+        let line: Waveform = "720p60"
+            .parse::<VideoFormat>()
+            .unwrap()
+            .reference_waveform(WaveformSpan::Line, 1);
+        assert_eq!(line.to_f32le().len(), 1650 * 4);
+        let csv = line.to_csv();
+        assert!(csv.starts_with("time_us,millivolts\n0.000000,"));
+        assert_eq!(csv.lines().count(), 1651);
+        assert_eq!(
+            WaveformFileFormat::from_path("sync.raw"),
+            Some(WaveformFileFormat::F32)
+        );
+    }
+}